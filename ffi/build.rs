@@ -0,0 +1,23 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Set to write the header to `include/gtp.h` too, to update the committed copy.
+const UPDATE_HEADER: &str = "GTP_FFI_UPDATE_HEADER";
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is set by cargo"));
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).expect("Invalid cbindgen.toml");
+    let bindings = cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Unable to generate the C header");
+    bindings.write_to_file(out_dir.join("include/gtp.h"));
+    if env::var_os(UPDATE_HEADER).is_some() {
+        bindings.write_to_file(crate_dir.join("include/gtp.h"));
+    }
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed={UPDATE_HEADER}");
+}
@@ -0,0 +1,140 @@
+/* Generated by cbindgen from ffi/src/lib.rs: do not edit. */
+
+#ifndef GTP_H
+#define GTP_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Value of the empty cells in piece-id grids.
+#define GTP_EMPTY_CELL UINT8_MAX
+
+// Result of the functions of the library.
+typedef enum GtpStatus {
+  GTP_STATUS_OK = 0,
+  // A required pointer is null.
+  GTP_STATUS_NULL_POINTER = 1,
+  // A piece id is unknown, or a shape is not a valid piece.
+  GTP_STATUS_INVALID_PIECE = 2,
+  // The pieces cannot fill a board of the given number of columns.
+  GTP_STATUS_INVALID_GAME = 3,
+  // A board does not have the number of cells of the game, or holds unknown piece ids.
+  GTP_STATUS_INVALID_BOARD = 4,
+  // The buffer given by the caller cannot hold the result.
+  GTP_STATUS_BUFFER_TOO_SMALL = 5,
+} GtpStatus;
+
+// A game and its precomputed piece placements.
+typedef struct GtpGame GtpGame;
+
+// Shape of a custom piece.
+typedef struct GtpShape {
+  // `width * height` cells, row by row, non-zero for the cells of the piece.
+  const uint8_t *cells;
+  size_t width;
+  size_t height;
+  // Color of the piece, as a 24-bit RGB value.
+  uint32_t color;
+} GtpShape;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns a static description of `status`, which must not be freed.
+const char *gtp_status_message(enum GtpStatus status);
+
+// Creates a game from pieces of the original game, `piece_ids` being indices in the list of
+// piece names, from 0 for the red square to 15 for the blue S.
+//
+// # Safety
+//
+// `piece_ids` must point to `count` bytes, and `out` must be valid for writes.
+enum GtpStatus gtp_game_from_piece_ids(const uint8_t *piece_ids,
+                                       size_t count,
+                                       uint32_t columns,
+                                       struct GtpGame **out);
+
+// Creates a game from the shapes of custom pieces.
+//
+// # Safety
+//
+// `shapes` must point to `count` shapes, whose `cells` point to `width * height` bytes,
+// and `out` must be valid for writes.
+enum GtpStatus gtp_game_from_shapes(const struct GtpShape *shapes,
+                                    size_t count,
+                                    uint32_t columns,
+                                    struct GtpGame **out);
+
+// Releases a game. Does nothing if `game` is null.
+//
+// # Safety
+//
+// `game` must be null or returned by a `gtp_game_from_*` function, and not already freed.
+void gtp_game_free(struct GtpGame *game);
+
+// Returns the number of columns of the board, or 0 if `game` is null.
+//
+// # Safety
+//
+// `game` must be null or a valid game.
+uint32_t gtp_game_columns(const struct GtpGame *game);
+
+// Returns the number of rows of the board, or 0 if `game` is null.
+//
+// # Safety
+//
+// `game` must be null or a valid game.
+uint32_t gtp_game_rows(const struct GtpGame *game);
+
+// Counts the solutions of the game into `count`.
+//
+// # Safety
+//
+// `game` must be a valid game, and `count` valid for writes.
+enum GtpStatus gtp_game_count(const struct GtpGame *game, uint64_t *count);
+
+// Writes the solutions of page `page_index` of `page_size` solutions into `cells`, one
+// piece-id grid after the other, and their number into `written`.
+//
+// `cells` must hold at least `page_size * rows * columns` bytes; the last page may have
+// fewer solutions. Solutions come in the same order for every page size.
+//
+// # Safety
+//
+// `game` must be a valid game, `cells` must point to `cells_len` writable bytes, and
+// `written` must be valid for writes.
+enum GtpStatus gtp_game_solutions(const struct GtpGame *game,
+                                  size_t page_index,
+                                  size_t page_size,
+                                  uint8_t *cells,
+                                  size_t cells_len,
+                                  size_t *written);
+
+// Renders a piece-id grid of `rows * columns` cells, such as a solution, as an SVG document
+// written to `svg`, to be released with `gtp_string_free`.
+//
+// # Safety
+//
+// `game` must be a valid game, `cells` must point to `cells_len` bytes, and `svg` must be
+// valid for writes.
+enum GtpStatus gtp_game_render_svg(const struct GtpGame *game,
+                                   const uint8_t *cells,
+                                   size_t cells_len,
+                                   char **svg);
+
+// Releases a string returned by the library. Does nothing if `string` is null.
+//
+// # Safety
+//
+// `string` must be null or returned by the library, and not already freed.
+void gtp_string_free(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GTP_H */
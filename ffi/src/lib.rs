@@ -0,0 +1,372 @@
+//! C bindings for the core `gtp-lib` library.
+//!
+//! The C header `include/gtp.h` is generated from this file by cbindgen when the crate is built.
+//!
+//! # Conventions
+//!
+//! - Functions return a [`GtpStatus`], and their results through pointers given by the caller.
+//! - Games are opaque [`GtpGame`] pointers, released with [`gtp_game_free`].
+//! - Boards are piece-id grids: one byte per cell, row by row, holding the index of the piece in
+//!   the game, or [`GTP_EMPTY_CELL`] for empty cells.
+//! - Strings returned by the library are released with [`gtp_string_free`].
+
+use std::ffi::{c_char, CString};
+use std::slice;
+
+use nalgebra::DMatrix;
+
+use gtp_lib::game_resolver::{piece_index, with_piece_index};
+use gtp_lib::svg_renderer::{svg_from_matrix_with_options, SvgOptions};
+use gtp_lib::{Game, Piece, PieceName, PlacementTable};
+
+/// Value of the empty cells in piece-id grids.
+pub const GTP_EMPTY_CELL: u8 = u8::MAX;
+
+/// Result of the functions of the library.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GtpStatus {
+    Ok = 0,
+    /// A required pointer is null.
+    NullPointer = 1,
+    /// A piece id is unknown, or a shape is not a valid piece.
+    InvalidPiece = 2,
+    /// The pieces cannot fill a board of the given number of columns.
+    InvalidGame = 3,
+    /// A board does not have the number of cells of the game, or holds unknown piece ids.
+    InvalidBoard = 4,
+    /// The buffer given by the caller cannot hold the result.
+    BufferTooSmall = 5,
+}
+
+/// Shape of a custom piece.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GtpShape {
+    /// `width * height` cells, row by row, non-zero for the cells of the piece.
+    pub cells: *const u8,
+    pub width: usize,
+    pub height: usize,
+    /// Color of the piece, as a 24-bit RGB value.
+    pub color: u32,
+}
+
+/// A game and its precomputed piece placements.
+pub struct GtpGame {
+    game: Game,
+    table: PlacementTable,
+}
+
+/// Returns a static description of `status`, which must not be freed.
+#[no_mangle]
+pub extern "C" fn gtp_status_message(status: GtpStatus) -> *const c_char {
+    let message: &'static [u8] = match status {
+        GtpStatus::Ok => b"success\0",
+        GtpStatus::NullPointer => b"a required pointer is null\0",
+        GtpStatus::InvalidPiece => b"invalid piece\0",
+        GtpStatus::InvalidGame => b"the pieces cannot fill complete rows of the board\0",
+        GtpStatus::InvalidBoard => b"invalid board\0",
+        GtpStatus::BufferTooSmall => b"the buffer is too small\0",
+    };
+    message.as_ptr().cast()
+}
+
+/// Validates `game` and moves it into a new `GtpGame` written to `out`.
+///
+/// # Safety
+///
+/// `out` must be valid for writes.
+unsafe fn new_game(game: Game, out: *mut *mut GtpGame) -> GtpStatus {
+    if game.validate().is_err() {
+        return GtpStatus::InvalidGame;
+    }
+    let table = PlacementTable::new(&game);
+    unsafe { *out = Box::into_raw(Box::new(GtpGame { game, table })) };
+    GtpStatus::Ok
+}
+
+/// Creates a game from pieces of the original game, `piece_ids` being indices in the list of
+/// piece names, from 0 for the red square to 15 for the blue S.
+///
+/// # Safety
+///
+/// `piece_ids` must point to `count` bytes, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_game_from_piece_ids(
+    piece_ids: *const u8,
+    count: usize,
+    columns: u32,
+    out: *mut *mut GtpGame,
+) -> GtpStatus {
+    if piece_ids.is_null() || out.is_null() {
+        return GtpStatus::NullPointer;
+    }
+    let ids = unsafe { slice::from_raw_parts(piece_ids, count) };
+    let Some(pieces) = ids.iter().map(|&id| PieceName::ALL.get(usize::from(id)).map(PieceName::piece)).collect() else {
+        return GtpStatus::InvalidPiece;
+    };
+    unsafe { new_game(Game { columns, pieces }, out) }
+}
+
+/// Creates a game from the shapes of custom pieces.
+///
+/// # Safety
+///
+/// `shapes` must point to `count` shapes, whose `cells` point to `width * height` bytes,
+/// and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_game_from_shapes(
+    shapes: *const GtpShape,
+    count: usize,
+    columns: u32,
+    out: *mut *mut GtpGame,
+) -> GtpStatus {
+    if shapes.is_null() || out.is_null() {
+        return GtpStatus::NullPointer;
+    }
+    let shapes = unsafe { slice::from_raw_parts(shapes, count) };
+    let mut pieces = Vec::with_capacity(shapes.len());
+    for shape in shapes {
+        if shape.cells.is_null() {
+            return GtpStatus::NullPointer;
+        }
+        let Some(size) = shape.width.checked_mul(shape.height) else {
+            return GtpStatus::InvalidPiece;
+        };
+        let cells = unsafe { slice::from_raw_parts(shape.cells, size) };
+        let matrix = DMatrix::from_row_iterator(shape.height, shape.width, cells.iter().map(|&cell| u32::from(cell)));
+        match Piece::from_shape(&matrix, shape.color) {
+            Ok(piece) => pieces.push(piece),
+            Err(_) => return GtpStatus::InvalidPiece,
+        }
+    }
+    unsafe { new_game(Game { columns, pieces }, out) }
+}
+
+/// Releases a game. Does nothing if `game` is null.
+///
+/// # Safety
+///
+/// `game` must be null or returned by a `gtp_game_from_*` function, and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_game_free(game: *mut GtpGame) {
+    if !game.is_null() {
+        drop(unsafe { Box::from_raw(game) });
+    }
+}
+
+/// Returns the number of columns of the board, or 0 if `game` is null.
+///
+/// # Safety
+///
+/// `game` must be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_game_columns(game: *const GtpGame) -> u32 {
+    unsafe { game.as_ref() }.map_or(0, |game| game.game.columns)
+}
+
+/// Returns the number of rows of the board, or 0 if `game` is null.
+///
+/// # Safety
+///
+/// `game` must be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_game_rows(game: *const GtpGame) -> u32 {
+    unsafe { game.as_ref() }.map_or(0, |game| game.game.rows())
+}
+
+/// Counts the solutions of the game into `count`.
+///
+/// # Safety
+///
+/// `game` must be a valid game, and `count` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_game_count(game: *const GtpGame, count: *mut u64) -> GtpStatus {
+    let Some(game) = (unsafe { game.as_ref() }) else {
+        return GtpStatus::NullPointer;
+    };
+    if count.is_null() {
+        return GtpStatus::NullPointer;
+    }
+    unsafe { *count = game.table.count() as u64 };
+    GtpStatus::Ok
+}
+
+/// Writes the solutions of page `page_index` of `page_size` solutions into `cells`, one
+/// piece-id grid after the other, and their number into `written`.
+///
+/// `cells` must hold at least `page_size * rows * columns` bytes; the last page may have
+/// fewer solutions. Solutions come in the same order for every page size.
+///
+/// # Safety
+///
+/// `game` must be a valid game, `cells` must point to `cells_len` writable bytes, and
+/// `written` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_game_solutions(
+    game: *const GtpGame,
+    page_index: usize,
+    page_size: usize,
+    cells: *mut u8,
+    cells_len: usize,
+    written: *mut usize,
+) -> GtpStatus {
+    let Some(game) = (unsafe { game.as_ref() }) else {
+        return GtpStatus::NullPointer;
+    };
+    if cells.is_null() || written.is_null() {
+        return GtpStatus::NullPointer;
+    }
+    let size = game.game.cells() as usize;
+    if page_size.checked_mul(size).is_none_or(|needed| needed > cells_len) {
+        return GtpStatus::BufferTooSmall;
+    }
+
+    let cells = unsafe { slice::from_raw_parts_mut(cells, cells_len) };
+    let solutions = game.table.page(page_index, page_size);
+    for (solution, grid) in solutions.iter().zip(cells.chunks_exact_mut(size)) {
+        // Row by row, while nalgebra stores matrices column by column
+        for (cell, value) in grid.iter_mut().zip(solution.transpose().iter()) {
+            *cell = piece_index(*value).and_then(|id| u8::try_from(id).ok()).unwrap_or(GTP_EMPTY_CELL);
+        }
+    }
+    unsafe { *written = solutions.len() };
+    GtpStatus::Ok
+}
+
+/// Renders a piece-id grid of `rows * columns` cells, such as a solution, as an SVG document
+/// written to `svg`, to be released with `gtp_string_free`.
+///
+/// # Safety
+///
+/// `game` must be a valid game, `cells` must point to `cells_len` bytes, and `svg` must be
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_game_render_svg(
+    game: *const GtpGame,
+    cells: *const u8,
+    cells_len: usize,
+    svg: *mut *mut c_char,
+) -> GtpStatus {
+    let Some(game) = (unsafe { game.as_ref() }) else {
+        return GtpStatus::NullPointer;
+    };
+    if cells.is_null() || svg.is_null() {
+        return GtpStatus::NullPointer;
+    }
+    let cells = unsafe { slice::from_raw_parts(cells, cells_len) };
+    let game = &game.game;
+    if cells_len != game.cells() as usize {
+        return GtpStatus::InvalidBoard;
+    }
+    let columns = game.columns as usize;
+    let mut matrix = DMatrix::zeros(cells_len / columns, columns);
+    for (index, &id) in cells.iter().enumerate() {
+        if id == GTP_EMPTY_CELL {
+            continue;
+        }
+        let Some(piece) = game.piece(usize::from(id)) else {
+            return GtpStatus::InvalidBoard;
+        };
+        matrix[(index / columns, index % columns)] = with_piece_index(piece.color, usize::from(id));
+    }
+
+    let document = svg_from_matrix_with_options(&matrix, &SvgOptions::default());
+    // The SVG renderer never writes NUL bytes
+    let document = CString::new(document).expect("SVG without NUL bytes");
+    unsafe { *svg = document.into_raw() };
+    GtpStatus::Ok
+}
+
+/// Releases a string returned by the library. Does nothing if `string` is null.
+///
+/// # Safety
+///
+/// `string` must be null or returned by the library, and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn gtp_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use std::ptr;
+
+    /// Red square, tan bar and brown L on 3 columns, with 12 solutions.
+    fn game() -> *mut GtpGame {
+        let mut game = ptr::null_mut();
+        assert_eq!(unsafe { gtp_game_from_piece_ids([0, 1, 2].as_ptr(), 3, 3, &mut game) }, GtpStatus::Ok);
+        game
+    }
+
+    #[test]
+    fn test_game_from_piece_ids() {
+        let game = game();
+        unsafe {
+            assert_eq!((gtp_game_columns(game), gtp_game_rows(game)), (3, 2));
+            let mut count = 0;
+            assert_eq!(gtp_game_count(game, &mut count), GtpStatus::Ok);
+            assert_eq!(count, 12);
+            gtp_game_free(game);
+
+            let mut game = ptr::null_mut();
+            assert_eq!(gtp_game_from_piece_ids([0, 16].as_ptr(), 2, 3, &mut game), GtpStatus::InvalidPiece);
+            assert_eq!(gtp_game_from_piece_ids([0, 1].as_ptr(), 2, 2, &mut game), GtpStatus::InvalidGame);
+            assert!(game.is_null());
+            assert_eq!(gtp_game_count(ptr::null(), &mut count), GtpStatus::NullPointer);
+        }
+    }
+
+    #[test]
+    fn test_game_from_shapes() {
+        let square = [1];
+        let l = [1, 0, 1, 1];
+        let shapes = [
+            GtpShape { cells: square.as_ptr(), width: 1, height: 1, color: 0xFF0000 },
+            GtpShape { cells: l.as_ptr(), width: 2, height: 2, color: 0x8B4513 },
+        ];
+        let mut game = ptr::null_mut();
+        unsafe {
+            assert_eq!(gtp_game_from_shapes(shapes.as_ptr(), 2, 2, &mut game), GtpStatus::Ok);
+            let mut count = 0;
+            assert_eq!(gtp_game_count(game, &mut count), GtpStatus::Ok);
+            assert_eq!(count, 4);
+            gtp_game_free(game);
+
+            let split = [1, 0, 0, 1];
+            let shapes = [shapes[0], GtpShape { cells: split.as_ptr(), ..shapes[1] }];
+            assert_eq!(gtp_game_from_shapes(shapes.as_ptr(), 2, 2, &mut game), GtpStatus::InvalidPiece);
+        }
+    }
+
+    #[test]
+    fn test_solutions_and_svg() {
+        let game = game();
+        let mut cells = [0u8; 5 * 6];
+        let mut written = 0;
+        unsafe {
+            assert_eq!(gtp_game_solutions(game, 0, 6, cells.as_mut_ptr(), cells.len(), &mut written), GtpStatus::BufferTooSmall);
+            assert_eq!(gtp_game_solutions(game, 2, 5, cells.as_mut_ptr(), cells.len(), &mut written), GtpStatus::Ok);
+            // The last page holds the 2 remaining solutions
+            assert_eq!(written, 2);
+
+            let table = PlacementTable::new(&(*game).game);
+            let expected = &table.page(10, 1)[0];
+            assert_eq!(piece_index(expected[(1, 2)]).unwrap(), usize::from(cells[5]));
+
+            let mut svg = ptr::null_mut();
+            assert_eq!(gtp_game_render_svg(game, cells.as_ptr(), 6, &mut svg), GtpStatus::Ok);
+            assert!(CStr::from_ptr(svg).to_str().unwrap().starts_with("<svg"));
+            gtp_string_free(svg);
+            assert_eq!(gtp_game_render_svg(game, [9; 6].as_ptr(), 6, &mut svg), GtpStatus::InvalidBoard);
+            assert_eq!(gtp_game_render_svg(game, cells.as_ptr(), 5, &mut svg), GtpStatus::InvalidBoard);
+
+            assert_eq!(CStr::from_ptr(gtp_status_message(GtpStatus::BufferTooSmall)).to_str().unwrap(), "the buffer is too small");
+            gtp_game_free(game);
+        }
+    }
+}
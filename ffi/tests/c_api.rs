@@ -0,0 +1,32 @@
+//! Builds `test_gtp.c` against the static library and the header generated by the build, and runs it.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn test_c_program() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Integration tests run from `target/<profile>/deps`, next to which cargo puts the libraries
+    let target_dir = env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    let program = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("test_gtp");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+
+    let status = Command::new(compiler)
+        .arg(crate_dir.join("tests/test_gtp.c"))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(PathBuf::from(env!("OUT_DIR")).join("include"))
+        .arg(target_dir.join("libgtp_ffi.a"))
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .expect("C compiler not found, set CC");
+    assert!(status.success(), "the C test program does not build");
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
@@ -0,0 +1,98 @@
+/* Links against the library through the generated header, as a C application would. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "gtp.h"
+
+#define CHECK(condition)                                                   \
+    do {                                                                   \
+        if (!(condition)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+static int test_piece_ids(void) {
+    /* Red square, tan bar and brown L */
+    const uint8_t piece_ids[] = {0, 1, 2};
+    GtpGame *game = NULL;
+    CHECK(gtp_game_from_piece_ids(piece_ids, 3, 3, &game) == GTP_STATUS_OK);
+    CHECK(gtp_game_columns(game) == 3 && gtp_game_rows(game) == 2);
+
+    uint64_t count = 0;
+    CHECK(gtp_game_count(game, &count) == GTP_STATUS_OK);
+    CHECK(count == 12);
+
+    /* All the solutions, one page at a time */
+    size_t cells = (size_t)gtp_game_rows(game) * gtp_game_columns(game);
+    uint8_t *page = malloc(5 * cells);
+    size_t written = 0, total = 0;
+    for (size_t index = 0;; index++) {
+        CHECK(gtp_game_solutions(game, index, 5, page, 5 * cells, &written) == GTP_STATUS_OK);
+        for (size_t cell = 0; cell < written * cells; cell++) {
+            CHECK(page[cell] < 3);
+        }
+        total += written;
+        if (written < 5) {
+            break;
+        }
+    }
+    CHECK(total == 12);
+    CHECK(gtp_game_solutions(game, 0, 6, page, 5 * cells, &written) == GTP_STATUS_BUFFER_TOO_SMALL);
+
+    char *svg = NULL;
+    CHECK(gtp_game_render_svg(game, page, cells, &svg) == GTP_STATUS_OK);
+    CHECK(strncmp(svg, "<svg", 4) == 0);
+    CHECK(strstr(svg, "data-piece-id=\"2\"") != NULL);
+    gtp_string_free(svg);
+
+    free(page);
+    gtp_game_free(game);
+    return 0;
+}
+
+static int test_shapes(void) {
+    const uint8_t square[] = {1};
+    const uint8_t l[] = {1, 0,
+                         1, 1};
+    const GtpShape shapes[] = {
+        {square, 1, 1, 0xFF0000},
+        {l, 2, 2, 0x8B4513},
+    };
+    GtpGame *game = NULL;
+    CHECK(gtp_game_from_shapes(shapes, 2, 2, &game) == GTP_STATUS_OK);
+    uint64_t count = 0;
+    CHECK(gtp_game_count(game, &count) == GTP_STATUS_OK);
+    CHECK(count == 4);
+
+    const uint8_t board[] = {0, 1,
+                             GTP_EMPTY_CELL, 1};
+    char *svg = NULL;
+    CHECK(gtp_game_render_svg(game, board, 4, &svg) == GTP_STATUS_OK);
+    gtp_string_free(svg);
+    gtp_game_free(game);
+
+    /* Errors */
+    const uint8_t split[] = {1, 0,
+                             0, 1};
+    const GtpShape invalid[] = {
+        {square, 1, 1, 0xFF0000},
+        {split, 2, 2, 0x8B4513},
+    };
+    CHECK(gtp_game_from_shapes(invalid, 2, 2, &game) == GTP_STATUS_INVALID_PIECE);
+    CHECK(gtp_game_from_shapes(shapes, 2, 3, &game) == GTP_STATUS_INVALID_GAME);
+    CHECK(gtp_game_count(NULL, &count) == GTP_STATUS_NULL_POINTER);
+    CHECK(strcmp(gtp_status_message(GTP_STATUS_INVALID_GAME), "the pieces cannot fill complete rows of the board") == 0);
+    gtp_game_free(NULL);
+    gtp_string_free(NULL);
+    return 0;
+}
+
+int main(void) {
+    if (test_piece_ids() != 0 || test_shapes() != 0) {
+        return 1;
+    }
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,188 @@
+use std::ffi::{c_int, c_void};
+use std::ptr;
+
+use pyo3::exceptions::{PyBufferError, PyIndexError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+
+use nalgebra::DMatrix;
+
+use gtp_lib::game_resolver::piece_index;
+
+/// Value of the empty cells in piece-id grids.
+pub const EMPTY: i8 = -1;
+
+/// Piece-id grids held in one buffer, with a shape of `(rows, columns)` for one grid,
+/// or `(count, rows, columns)` for several solutions.
+///
+/// The grids implement the buffer protocol, so `numpy.asarray` and `memoryview` read them
+/// as `int8` arrays without copying.
+#[pyclass(name = "Grids", module = "gtp", frozen)]
+pub struct Grids {
+    data: Vec<i8>,
+    shape: Vec<isize>,
+    strides: Vec<isize>,
+}
+
+/// Returns the piece id of each cell of `matrix`, row by row.
+fn grid_cells(matrix: &DMatrix<u32>) -> impl Iterator<Item = i8> + '_ {
+    // Row by row, while nalgebra stores matrices column by column
+    (0..matrix.nrows())
+        .flat_map(move |row| (0..matrix.ncols()).map(move |col| matrix[(row, col)]))
+        .map(|cell| piece_index(cell).and_then(|id| i8::try_from(id).ok()).unwrap_or(EMPTY))
+}
+
+impl Grids {
+    fn new(data: Vec<i8>, shape: &[usize]) -> Self {
+        let shape: Vec<isize> = shape.iter().map(|&size| isize::try_from(size).expect("Grid too large")).collect();
+        // C order: the last dimension is contiguous
+        let mut strides = vec![1; shape.len()];
+        for dimension in (0..shape.len().saturating_sub(1)).rev() {
+            strides[dimension] = strides[dimension + 1] * shape[dimension + 1];
+        }
+        Self { data, shape, strides }
+    }
+
+    /// Packs `solutions` of a board of `rows` by `columns` cells.
+    pub fn from_solutions(solutions: &[DMatrix<u32>], rows: usize, columns: usize) -> Self {
+        Self::new(solutions.iter().flat_map(grid_cells).collect(), &[solutions.len(), rows, columns])
+    }
+
+    pub fn from_matrix(matrix: &DMatrix<u32>) -> Self {
+        Self::new(grid_cells(matrix).collect(), &[matrix.nrows(), matrix.ncols()])
+    }
+
+    /// Returns the position of `index` among `length` items, counting from the end when negative.
+    fn position(index: isize, length: usize) -> Option<usize> {
+        if index < 0 { length.checked_sub(index.unsigned_abs()) } else { Some(index.unsigned_abs()) }
+            .filter(|&position| position < length)
+    }
+
+    fn dimension(&self, index: usize) -> usize {
+        self.shape[index].unsigned_abs()
+    }
+
+    /// Rows of cells of all the grids, one after the other.
+    fn rows(&self) -> Vec<Vec<i8>> {
+        let columns = self.dimension(self.shape.len() - 1);
+        self.data.chunks(columns.max(1)).map(<[i8]>::to_vec).collect()
+    }
+}
+
+#[pymethods]
+impl Grids {
+    #[getter]
+    fn shape<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTuple>> {
+        PyTuple::new(py, &self.shape)
+    }
+
+    fn __len__(&self) -> usize {
+        self.dimension(0)
+    }
+
+    /// Returns a solution of several grids, or a row of cells of a single grid.
+    fn __getitem__<'py>(&self, py: Python<'py>, index: isize) -> PyResult<Bound<'py, PyAny>> {
+        let length = self.__len__();
+        let position = Self::position(index, length)
+            .ok_or_else(|| PyIndexError::new_err(format!("index {index} out of range for {length} items")))?;
+        let size = self.data.len() / length;
+        let data = self.data[position * size..(position + 1) * size].to_vec();
+        if self.shape.len() == 3 {
+            Ok(Bound::new(py, Self::new(data, &[self.dimension(1), self.dimension(2)]))?.into_any())
+        } else {
+            Ok(data.into_pyobject(py)?.into_any())
+        }
+    }
+
+    /// Returns the cells as nested lists.
+    fn tolist<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let rows = self.rows();
+        if self.shape.len() == 3 {
+            let grids: Vec<Vec<Vec<i8>>> = rows.chunks(self.dimension(1).max(1)).map(<[Vec<i8>]>::to_vec).collect();
+            Ok(grids.into_pyobject(py)?.into_any())
+        } else {
+            Ok(rows.into_pyobject(py)?.into_any())
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        let shape: Vec<String> = self.shape.iter().map(ToString::to_string).collect();
+        format!("Grids(shape=({}))", shape.join(", "))
+    }
+
+    /// Exposes the cells as a read-only `int8` buffer.
+    ///
+    /// # Safety
+    ///
+    /// `view` must be a valid `Py_buffer` to fill, as passed by the Python interpreter.
+    unsafe fn __getbuffer__(slf: Bound<'_, Self>, view: *mut ffi::Py_buffer, flags: c_int) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if flags & ffi::PyBUF_WRITABLE == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("Grids are read-only"));
+        }
+        // The grids are frozen, so the pointers stay valid as long as the view holds a reference
+        let grids = slf.get();
+        unsafe {
+            (*view).buf = grids.data.as_ptr().cast_mut().cast::<c_void>();
+            (*view).len = isize::try_from(grids.data.len()).expect("Grid too large");
+            (*view).readonly = 1;
+            (*view).itemsize = 1;
+            (*view).format = if flags & ffi::PyBUF_FORMAT == ffi::PyBUF_FORMAT { c"b".as_ptr().cast_mut() } else { ptr::null_mut() };
+            (*view).ndim = c_int::try_from(grids.shape.len()).expect("Too many dimensions");
+            (*view).shape = if flags & ffi::PyBUF_ND == ffi::PyBUF_ND { grids.shape.as_ptr().cast_mut() } else { ptr::null_mut() };
+            (*view).strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES { grids.strides.as_ptr().cast_mut() } else { ptr::null_mut() };
+            (*view).suboffsets = ptr::null_mut();
+            (*view).internal = ptr::null_mut();
+            (*view).obj = slf.into_any().into_ptr();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtp_lib::game_resolver::with_piece_index;
+
+    #[test]
+    fn test_shape_and_strides() {
+        let grids = Grids::new(vec![0; 24], &[4, 2, 3]);
+        assert_eq!(grids.shape, vec![4, 2, 3]);
+        // One byte per cell, in C order
+        assert_eq!(grids.strides, vec![6, 3, 1]);
+
+        let grid = Grids::new(vec![0; 6], &[2, 3]);
+        assert_eq!(grid.strides, vec![3, 1]);
+        assert_eq!(Grids::new(Vec::new(), &[0, 2, 3]).strides, vec![6, 3, 1]);
+    }
+
+    #[test]
+    fn test_from_matrix() {
+        // Cells are read row by row, empty cells becoming `EMPTY`
+        let matrix = DMatrix::from_row_slice(2, 3, &[with_piece_index(1, 0), with_piece_index(1, 2), 0, with_piece_index(1, 1), 0, 0]);
+        let grid = Grids::from_matrix(&matrix);
+        assert_eq!(grid.shape, vec![2, 3]);
+        assert_eq!(grid.data, vec![0, 2, EMPTY, 1, EMPTY, EMPTY]);
+        assert_eq!(grid.rows(), vec![vec![0, 2, EMPTY], vec![1, EMPTY, EMPTY]]);
+
+        let grids = Grids::from_solutions(&[matrix.clone(), matrix], 2, 3);
+        assert_eq!(grids.shape, vec![2, 2, 3]);
+        assert_eq!(grids.data.len(), 12);
+    }
+
+    #[test]
+    fn test_position() {
+        assert_eq!(Grids::position(0, 3), Some(0));
+        assert_eq!(Grids::position(2, 3), Some(2));
+        assert_eq!(Grids::position(-1, 3), Some(2));
+        assert_eq!(Grids::position(-3, 3), Some(0));
+        assert_eq!(Grids::position(3, 3), None);
+        assert_eq!(Grids::position(-4, 3), None);
+        assert_eq!(Grids::position(isize::MIN, 3), None);
+        assert_eq!(Grids::position(isize::MAX, 3), None);
+        assert_eq!(Grids::position(0, 0), None);
+    }
+}
@@ -0,0 +1,20 @@
+//! Python bindings for the core `gtp-lib` library, as the `gtp` module.
+
+pub mod grids;
+pub mod py_models;
+
+use pyo3::prelude::*;
+
+use grids::{Grids, EMPTY};
+use py_models::{PyGame, PyPiece, PyPieceName, SolutionIterator};
+
+#[pymodule]
+fn gtp(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add("EMPTY", EMPTY)?;
+    module.add_class::<PyPieceName>()?;
+    module.add_class::<PyPiece>()?;
+    module.add_class::<PyGame>()?;
+    module.add_class::<Grids>()?;
+    module.add_class::<SolutionIterator>()?;
+    Ok(())
+}
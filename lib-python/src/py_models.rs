@@ -0,0 +1,296 @@
+use std::collections::VecDeque;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use nalgebra::DMatrix;
+
+use gtp_lib::game_resolver::with_piece_index;
+use gtp_lib::svg_renderer::{svg_from_matrix_with_options, SvgOptions};
+use gtp_lib::{Game, Piece, PieceName, PlacementTable, SolverSession};
+
+use crate::grids::{Grids, EMPTY};
+
+/// Number of piece placements tried by `SolutionIterator` between checks for new solutions.
+const PLACEMENTS_PER_STEP: usize = 10_000;
+
+fn value_error(error: impl ToString) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// One of the 16 pieces of the original game.
+#[pyclass(name = "PieceName", module = "gtp", frozen, eq, skip_from_py_object)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PyPieceName(PieceName);
+
+#[pymethods]
+impl PyPieceName {
+    /// All the piece names, from the smallest piece to the largest.
+    #[staticmethod]
+    fn all() -> Vec<Self> {
+        PieceName::ALL.into_iter().map(Self).collect()
+    }
+
+    /// Finds a piece by its name, such as `BlueT4` or `blue-t4`.
+    #[staticmethod]
+    fn from_name(name: &str) -> PyResult<Self> {
+        PieceName::from_name(name).map(Self).ok_or_else(|| value_error(format!("unknown piece: {name}")))
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        format!("{:?}", self.0)
+    }
+
+    #[getter]
+    fn letter(&self) -> char {
+        self.0.letter()
+    }
+
+    #[getter]
+    fn description(&self) -> &'static str {
+        self.0.description()
+    }
+
+    fn piece(&self) -> PyPiece {
+        PyPiece(self.0.piece())
+    }
+
+    fn __hash__(&self) -> u64 {
+        u64::from(self.0.letter())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PieceName.{:?}", self.0)
+    }
+}
+
+/// A piece: its shape, in which 1 marks the cells of the piece, and its color.
+#[pyclass(name = "Piece", module = "gtp", frozen, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct PyPiece(Piece);
+
+#[pymethods]
+impl PyPiece {
+    /// Creates a custom piece from its rows, in which non-zero values are the cells of the piece.
+    #[new]
+    fn new(shape: Vec<Vec<u32>>, color: u32) -> PyResult<Self> {
+        let columns = shape.first().map_or(0, Vec::len);
+        if shape.iter().any(|row| row.len() != columns) {
+            return Err(value_error("all the rows of the shape must have the same length"));
+        }
+        let matrix = DMatrix::from_row_iterator(shape.len(), columns, shape.into_iter().flatten());
+        Piece::from_shape(&matrix, color).map(Self).map_err(value_error)
+    }
+
+    /// Color of the piece, as a 24-bit RGB value.
+    #[getter]
+    fn color(&self) -> u32 {
+        self.0.color
+    }
+
+    #[getter]
+    fn cells(&self) -> u32 {
+        self.0.cells()
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.0.matrix.ncols()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.0.matrix.nrows()
+    }
+
+    /// Rows of the shape, with 1 for the cells of the piece and 0 elsewhere.
+    #[getter]
+    fn shape(&self) -> Vec<Vec<u32>> {
+        self.0.matrix.row_iter().map(|row| row.iter().map(|&cell| u32::from(cell != 0)).collect()).collect()
+    }
+
+    /// Name of the piece when its shape is one of the original game pieces.
+    #[getter]
+    fn name(&self) -> Option<PyPieceName> {
+        PieceName::from_matrix(&self.0.matrix).map(PyPieceName)
+    }
+
+    #[pyo3(signature = (patterns = false, letters = false))]
+    fn svg(&self, patterns: bool, letters: bool) -> String {
+        svg_from_matrix_with_options(&(self.0.matrix.clone() * self.0.color), &SvgOptions { patterns, letters })
+    }
+
+    fn __repr__(&self) -> String {
+        match PieceName::from_matrix(&self.0.matrix) {
+            Some(name) => format!("Piece({name:?})"),
+            None => format!("Piece({}x{}, {} cells, #{:06X})", self.width(), self.height(), self.cells(), self.0.color),
+        }
+    }
+}
+
+/// A game: pieces to place on a board with a fixed number of columns.
+///
+/// Boards and solutions are grids of piece ids, the index of each piece in `pieces`,
+/// with `EMPTY` (-1) for empty cells.
+#[pyclass(name = "Game", module = "gtp")]
+pub struct PyGame(Game);
+
+impl PyGame {
+    /// Returns the game, or the reason why it cannot be solved.
+    fn checked(&self) -> PyResult<&Game> {
+        self.0.validate().map_err(value_error)?;
+        Ok(&self.0)
+    }
+
+    /// Decodes rows of piece ids into a board, encoded like the solutions of the solver.
+    fn grid_to_matrix(&self, grid: Vec<Vec<i64>>) -> PyResult<DMatrix<u32>> {
+        let columns = self.0.columns as usize;
+        if grid.iter().any(|row| row.len() != columns) {
+            return Err(value_error(format!("every row must have {columns} cells")));
+        }
+        let cells = grid
+            .iter()
+            .flatten()
+            .map(|&id| match id {
+                id if id == i64::from(EMPTY) => Ok(0),
+                id => u8::try_from(id)
+                    .ok()
+                    .filter(|&id| id < u8::MAX)
+                    .map(|id| {
+                        let id = usize::from(id);
+                        with_piece_index(self.0.piece(id).map_or(0, |piece| piece.color), id)
+                    })
+                    .ok_or_else(|| value_error(format!("invalid piece id: {id}"))),
+            })
+            .collect::<PyResult<Vec<u32>>>()?;
+        Ok(DMatrix::from_row_slice(grid.len(), columns, &cells))
+    }
+
+    fn pack(&self, solutions: &[DMatrix<u32>]) -> Grids {
+        Grids::from_solutions(solutions, self.0.rows() as usize, self.0.columns as usize)
+    }
+}
+
+#[pymethods]
+impl PyGame {
+    #[new]
+    fn new(pieces: Vec<PyRef<'_, PyPiece>>, columns: u32) -> PyResult<Self> {
+        let game = Game { columns, pieces: pieces.iter().map(|piece| piece.0.clone()).collect() };
+        game.validate().map_err(value_error)?;
+        Ok(Self(game))
+    }
+
+    /// The game with the 18 pieces of the box, on 5 columns, from which to pick games with `subset`.
+    #[staticmethod]
+    fn with_all_pieces() -> Self {
+        Self(Game::game_with_all_pieces())
+    }
+
+    /// A game made of the pieces of this game at `piece_ids`, on the same number of columns.
+    fn subset(&self, piece_ids: Vec<usize>) -> PyResult<Self> {
+        if let Some(id) = piece_ids.iter().find(|&&id| id >= self.0.pieces.len()) {
+            return Err(value_error(format!("invalid piece id: {id}")));
+        }
+        Ok(Self(Game::game_from_game(&self.0, piece_ids)))
+    }
+
+    #[getter]
+    fn columns(&self) -> u32 {
+        self.0.columns
+    }
+
+    #[getter]
+    fn rows(&self) -> u32 {
+        self.0.rows()
+    }
+
+    #[getter]
+    fn cells(&self) -> u32 {
+        self.0.cells()
+    }
+
+    #[getter]
+    fn pieces(&self) -> Vec<PyPiece> {
+        self.0.pieces.iter().cloned().map(PyPiece).collect()
+    }
+
+    /// Whether the pieces exactly fill complete rows of the board.
+    #[getter]
+    fn is_valid(&self) -> bool {
+        self.0.validate().is_ok()
+    }
+
+    /// Finds the solutions, or the first `limit` ones, as a `(count, rows, columns)` grid.
+    ///
+    /// Solutions come in the same order from `solve`, `page` and `iterate`.
+    #[pyo3(signature = (limit = None))]
+    fn solve(&self, py: Python<'_>, limit: Option<usize>) -> PyResult<Grids> {
+        let game = self.checked()?;
+        let solutions = py.detach(|| PlacementTable::new(game).page(0, limit.unwrap_or(usize::MAX)));
+        Ok(self.pack(&solutions))
+    }
+
+    /// Counts the solutions without keeping them.
+    fn count(&self, py: Python<'_>) -> PyResult<usize> {
+        let game = self.checked()?;
+        Ok(py.detach(|| PlacementTable::new(game).count()))
+    }
+
+    /// Returns the page `page_index` of `page_size` solutions, in the order of `solve`.
+    fn page(&self, py: Python<'_>, page_index: usize, page_size: usize) -> PyResult<Grids> {
+        let game = self.checked()?;
+        let solutions = py.detach(|| PlacementTable::new(game).page(page_index, page_size));
+        Ok(self.pack(&solutions))
+    }
+
+    /// Iterates over the solutions, searching for each one when it is requested.
+    fn iterate(&self) -> PyResult<SolutionIterator> {
+        Ok(SolutionIterator { session: SolverSession::new(self.checked()?), pending: VecDeque::new() })
+    }
+
+    /// Checks that `grid` is a solution, raising a `ValueError` explaining the first problem found.
+    fn verify(&self, grid: Vec<Vec<i64>>) -> PyResult<()> {
+        let matrix = self.grid_to_matrix(grid)?;
+        self.0.verify(&matrix).map_err(value_error)
+    }
+
+    /// Renders a grid of piece ids, such as a solution, as an SVG document.
+    #[pyo3(signature = (grid, patterns = false, letters = false))]
+    fn svg(&self, grid: Vec<Vec<i64>>, patterns: bool, letters: bool) -> PyResult<String> {
+        let matrix = self.grid_to_matrix(grid)?;
+        Ok(svg_from_matrix_with_options(&matrix, &SvgOptions { patterns, letters }))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Game({} pieces, {} columns)", self.0.pieces.len(), self.0.columns)
+    }
+}
+
+/// Iterator over the solutions of a game, returned by `Game.iterate`.
+#[pyclass(module = "gtp")]
+pub struct SolutionIterator {
+    session: SolverSession,
+    pending: VecDeque<DMatrix<u32>>,
+}
+
+#[pymethods]
+impl SolutionIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> Option<Grids> {
+        while self.pending.is_empty() && !self.session.is_done() {
+            let solutions = py.detach(|| self.session.step(PLACEMENTS_PER_STEP));
+            self.pending.extend(solutions);
+        }
+        self.pending.pop_front().map(|solution| Grids::from_matrix(&solution))
+    }
+
+    /// Estimated part of the search already done, from 0 to 1.
+    #[getter]
+    fn progress(&self) -> f64 {
+        self.session.progress()
+    }
+}
@@ -0,0 +1,76 @@
+"""Tests of the Python bindings, run with pytest after `maturin develop`."""
+
+import pytest
+
+import gtp
+
+
+def small_game():
+    pieces = [gtp.PieceName.from_name(name).piece() for name in ["RedSquare1", "TanBar2", "BrownL3"]]
+    return gtp.Game(pieces, 3)
+
+
+def test_solve_page_and_iterate():
+    game = small_game()
+    assert game.count() == 12
+
+    solutions = game.solve()
+    assert solutions.shape == (12, 2, 3)
+    assert len(solutions) == 12
+    assert game.solve(limit=2).shape == (2, 2, 3)
+
+    # `solve`, `page` and `iterate` give the same solutions in the same order
+    all_solutions = solutions.tolist()
+    assert game.page(1, 5).tolist() == all_solutions[5:10]
+    assert game.page(2, 5).tolist() == all_solutions[10:]
+    assert [solution.tolist() for solution in game.iterate()] == all_solutions
+
+
+def test_indexing():
+    solutions = small_game().solve()
+    assert solutions[-1].tolist() == solutions.tolist()[-1]
+    assert solutions[0].shape == (2, 3)
+    assert solutions[0][-1] == solutions.tolist()[0][-1]
+    with pytest.raises(IndexError):
+        solutions[12]
+    with pytest.raises(IndexError):
+        solutions[-13]
+
+
+def test_buffer():
+    solutions = small_game().solve()
+    view = memoryview(solutions)
+    assert (view.format, view.itemsize, view.ndim, view.readonly) == ("b", 1, 3, True)
+    assert view.shape == (12, 2, 3)
+    assert view.strides == (6, 3, 1)
+    assert view.tolist() == solutions.tolist()
+
+
+def test_numpy():
+    np = pytest.importorskip("numpy")
+    solutions = small_game().solve()
+    array = np.asarray(solutions)
+    assert array.dtype == np.int8
+    assert array.shape == (12, 2, 3)
+    assert array.tolist() == solutions.tolist()
+    # Each cell is covered by exactly one piece in every solution
+    assert ((array >= 0).sum(axis=0) == 12).all()
+    small_game().verify(array[0])
+
+
+def test_verify():
+    game = small_game()
+    game.verify(game.solve()[0].tolist())
+
+    with pytest.raises(ValueError, match="is empty"):
+        game.verify([[gtp.EMPTY, 1, 1], [2, 2, 2]])
+    with pytest.raises(ValueError, match="every row must have 3 cells"):
+        game.verify([[0, 1], [2, 2]])
+    with pytest.raises(ValueError, match="invalid piece id: 300"):
+        game.verify([[300, 1, 1], [2, 2, 2]])
+
+
+def test_invalid_game():
+    pieces = [gtp.PieceName.from_name("RedSquare1").piece(), gtp.PieceName.from_name("TanBar2").piece()]
+    with pytest.raises(ValueError):
+        gtp.Game(pieces, 2)
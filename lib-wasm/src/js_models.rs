@@ -1,3 +1,6 @@
+use std::cell::{OnceCell, Ref, RefCell};
+use std::rc::Rc;
+
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use js_sys;
@@ -5,9 +8,14 @@ use js_sys;
 use nalgebra::DMatrix;
 
 use gtp_lib::models::*;
+use gtp_lib::palette::Palette;
 use gtp_lib::svg_renderer::*;
 use gtp_lib::game_resolver::*;
+use gtp_lib::dead_end;
+use gtp_lib::{DeadEnd, Hint, HintLevel, PlayError, PlayEvent, PlaySession, SolverSession};
 
+/// Value of the empty cells in piece-id grids exchanged with JavaScript.
+const EMPTY_CELL_ID: u8 = u8::MAX;
 
 // https://github.com/rustwasm/wasm-bindgen/pull/2633
 #[wasm_bindgen(getter_with_clone)]
@@ -19,9 +27,9 @@ pub struct JSMatrix {
 }
 
 impl JSMatrix {
-    pub fn new(matrix: &DMatrix<u32>) -> Self {
+    pub fn new(matrix: &DMatrix<u32>, style: &SvgOptions) -> Self {
         Self {
-            svg: svg_from_matrix(matrix),
+            svg: svg_from_matrix_with_options(matrix, style),
             width: matrix.ncols(),
             height: matrix.nrows()
         }
@@ -30,32 +38,329 @@ impl JSMatrix {
 
 
 
-#[wasm_bindgen(getter_with_clone)]
+/// The data of a piece of a game, shared by all its `JSPiece` handles.
+#[derive(Debug)]
+struct PieceData {
+    id: usize,
+    /// Id of the piece in the game it was picked from, see `JSGame.game_from_game`.
+    source_id: usize,
+    piece: Piece,
+    style: SvgOptions,
+    /// Rendered the first time it is read.
+    svg: OnceCell<String>,
+}
+
+/// A handle to a piece of a game.
+///
+/// Handles are cheap to copy: reading `JSGame.pieces` again returns handles to the same data,
+/// and the SVG of the piece is only rendered the first time it is read.
+#[wasm_bindgen]
 #[derive(Debug, Clone)]
 pub struct JSPiece {
-    pub id: usize,
-    pub matrix: JSMatrix,
-    pub color: u32,
-    pub cells: u32,
+    data: Rc<PieceData>,
 }
 
 impl JSPiece {
-    pub fn new(id: usize, piece: &Piece) -> Self {
+    pub fn new(id: usize, piece: &Piece, style: &SvgOptions) -> Self {
+        Self::with_source_id(id, id, piece, style)
+    }
+
+    fn with_source_id(id: usize, source_id: usize, piece: &Piece, style: &SvgOptions) -> Self {
+        Self {
+            data: Rc::new(PieceData { id, source_id, piece: piece.clone(), style: *style, svg: OnceCell::new() }),
+        }
+    }
+
+    pub fn piece(&self) -> &Piece {
+        &self.data.piece
+    }
+}
+
+#[wasm_bindgen]
+impl JSPiece {
+    /// Creates a custom piece, to build a `JSGame` with.
+    ///
+    /// `shape` holds `width` cells per row, row by row, non-zero for the cells of the piece,
+    /// and `color` is a 24-bit RGB value such as `0xDA0022`. The `id` of the piece is 0 until
+    /// it is part of a game. Throws an error when the shape is empty or not in one block.
+    #[wasm_bindgen(constructor)]
+    pub fn from_shape(shape: &[u8], width: usize, color: u32) -> Result<JSPiece, JsError> {
+        if width == 0 || !shape.len().is_multiple_of(width) {
+            return Err(JsError::new(&format!("Expected a multiple of {width} cells, got {}", shape.len())));
+        }
+        let shape = DMatrix::from_fn(shape.len() / width, width, |row, col| u32::from(shape[row * width + col]));
+        let piece = Piece::from_shape(&shape, color).map_err(|error| JsError::new(&error.to_string()))?;
+        Ok(Self::new(0, &piece, &SvgOptions::default()))
+    }
+
+    /// Index of the piece in its game.
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> usize {
+        self.data.id
+    }
+
+    /// Id of the piece in the game it was picked from with `JSGame.game_from_game`, or `id` for other games.
+    ///
+    /// This is the `data-piece-id` of the piece in the SVGs.
+    #[wasm_bindgen(getter)]
+    pub fn source_id(&self) -> usize {
+        self.data.source_id
+    }
+
+    /// Color of the piece, as a 24-bit RGB value.
+    #[wasm_bindgen(getter)]
+    pub fn color(&self) -> u32 {
+        self.data.piece.color
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cells(&self) -> u32 {
+        self.data.piece.cells()
+    }
+
+    /// Width of the piece, in cells.
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.data.piece.matrix.ncols()
+    }
+
+    /// Height of the piece, in cells.
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.data.piece.matrix.nrows()
+    }
+
+    /// SVG of the piece, with the same `data-piece-id` as on the boards of its game.
+    #[wasm_bindgen(getter)]
+    pub fn svg(&self) -> String {
+        let data = &self.data;
+        data.svg
+            .get_or_init(|| {
+                let piece = &data.piece;
+                svg_from_matrix_with_options(&(piece.matrix.clone() * with_piece_index(piece.color, data.source_id)), &data.style)
+            })
+            .clone()
+    }
+
+    /// The SVG and size of the piece as a `JSMatrix`; `svg`, `width` and `height` avoid the extra object.
+    #[wasm_bindgen(getter)]
+    pub fn matrix(&self) -> JSMatrix {
+        JSMatrix { svg: self.svg(), width: self.width(), height: self.height() }
+    }
+}
+
+
+
+/// Where a piece lies in a solution.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct JSPlacement {
+    /// `JSPiece.id` of the piece.
+    pub piece_id: usize,
+    /// Index of the orientation of the piece, among its distinct rotations and reflections.
+    pub orientation: usize,
+    /// Row of the top of the piece.
+    pub row: usize,
+    /// Column of the left of the piece.
+    pub col: usize,
+}
+
+impl From<Placement> for JSPlacement {
+    fn from(placement: Placement) -> Self {
+        Self { piece_id: placement.piece, orientation: placement.orientation, row: placement.row, col: placement.col }
+    }
+}
+
+/// A solution as typed data, to be rendered on demand with `JSGame.render_grid`.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct JSSolution {
+    grid: Vec<u8>,
+    width: usize,
+    height: usize,
+    placements: Vec<JSPlacement>,
+}
+
+/// Returns the piece id of each cell of `matrix`, row by row.
+fn grid_cells(matrix: &DMatrix<u32>) -> impl Iterator<Item = u8> + '_ {
+    // Row by row, while nalgebra stores matrices column by column
+    (0..matrix.nrows())
+        .flat_map(move |row| (0..matrix.ncols()).map(move |col| matrix[(row, col)]))
+        .map(|cell| piece_index(cell).and_then(|id| u8::try_from(id).ok()).unwrap_or(EMPTY_CELL_ID))
+}
+
+impl JSSolution {
+    pub fn new(game: &Game, matrix: &DMatrix<u32>) -> Self {
+        let grid = grid_cells(matrix).collect();
+        let placements = game.placements(matrix).expect("Invalid solution")
+            .into_iter()
+            .map(JSPlacement::from)
+            .collect();
+        Self { grid, width: matrix.ncols(), height: matrix.nrows(), placements }
+    }
+}
+
+#[wasm_bindgen]
+impl JSSolution {
+    /// `JSPiece.id` of the piece covering each cell, row by row, as a `Uint8Array`.
+    #[wasm_bindgen(getter)]
+    pub fn grid(&self) -> Vec<u8> {
+        self.grid.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Placement of each piece, in the order of `JSGame.pieces`.
+    #[wasm_bindgen(getter)]
+    pub fn placements(&self) -> JSPlacementArray {
+        self.placements.iter()
+        .copied()
+        .map(JsValue::from)
+        .collect::<js_sys::Array>()
+        .unchecked_into::<JSPlacementArray>()
+    }
+}
+
+/// Solutions packed into a single buffer, one byte per cell.
+///
+/// Solution `i` is `data[i * width * height .. (i + 1) * width * height]`, the `JSPiece.id` of
+/// the piece covering each cell, row by row. Thousands of solutions take a few hundred
+/// kilobytes, and are only rendered when shown, with `svg(index)`.
+#[wasm_bindgen]
+pub struct JSSolutionBatch {
+    data: Vec<u8>,
+    width: usize,
+    height: usize,
+    game: Game,
+    style: SvgOptions,
+    source_ids: Rc<[usize]>,
+}
+
+impl JSSolutionBatch {
+    fn new(game: &JSGame, solutions: &[DMatrix<u32>]) -> Self {
         Self {
-            id,
-            matrix: JSMatrix::new(&(piece.matrix.clone() * piece.color)),
-            color: piece.color,
-            cells: piece.cells() as u32,
+            data: solutions.iter().flat_map(grid_cells).collect(),
+            width: game.game.columns as usize,
+            height: game.game.rows() as usize,
+            game: Game { columns: game.game.columns, pieces: game.game.pieces.clone() },
+            style: game.style,
+            source_ids: Rc::clone(&game.source_ids),
+        }
+    }
+
+    fn cells(&self, index: usize) -> Result<&[u8], JsError> {
+        let size = self.width * self.height;
+        if index >= self.count() {
+            return Err(JsError::new(&format!("No solution {index}, the batch has {}", self.count())));
         }
+        Ok(&self.data[index * size..(index + 1) * size])
     }
 }
 
+#[wasm_bindgen]
+impl JSSolutionBatch {
+    /// Number of solutions in the batch.
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.data.len().checked_div(self.width * self.height).unwrap_or(0)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// All the solutions as one `Uint8Array`, a view into the WASM memory rather than a copy.
+    ///
+    /// The view is invalidated when the batch is freed, and when the WASM memory grows, which
+    /// any other call into the module may do: read it right away, or copy it with `slice()`.
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> js_sys::Uint8Array {
+        // SAFETY: the view is handed to JS without allocating in between, and the documentation
+        // above tells callers not to keep it past the lifetime of the batch or a memory growth.
+        unsafe { js_sys::Uint8Array::view(&self.data) }
+    }
+
+    /// Piece-id grid of the solution at `index`, as in `JSSolution.grid`.
+    pub fn grid(&self, index: usize) -> Result<Vec<u8>, JsError> {
+        self.cells(index).map(<[u8]>::to_vec)
+    }
+
+    /// The solution at `index` with its placements.
+    pub fn solution(&self, index: usize) -> Result<JSSolution, JsError> {
+        let matrix = grid_to_matrix(&self.game, self.cells(index)?)?;
+        Ok(JSSolution::new(&self.game, &matrix))
+    }
+
+    /// Renders the solution at `index` with the style of the game when the batch was made.
+    pub fn svg(&self, index: usize) -> Result<String, JsError> {
+        let matrix = grid_to_matrix(&self.game, self.cells(index)?)?;
+        Ok(render_board(&matrix, &self.style, &self.source_ids))
+    }
+}
+
+/// Decodes a piece-id grid of `game` into a board, encoded like the solutions of the solver.
+fn grid_to_matrix(game: &Game, cells: &[u8]) -> Result<DMatrix<u32>, JsError> {
+    let columns = game.columns as usize;
+    if columns == 0 || !cells.len().is_multiple_of(columns) {
+        return Err(JsError::new(&format!("Expected a multiple of {columns} cells, got {}", cells.len())));
+    }
+    Ok(DMatrix::from_fn(cells.len() / columns, columns, |row, col| match cells[row * columns + col] {
+        EMPTY_CELL_ID => 0,
+        id => {
+            let color = game.piece(usize::from(id)).map_or(0, |piece| piece.color);
+            with_piece_index(color, usize::from(id))
+        }
+    }))
+}
+
+/// Renders a board of a game whose pieces have `source_ids` in the game they were picked from,
+/// so that `data-piece-id` is the id of each piece in that game.
+fn render_board(matrix: &DMatrix<u32>, style: &SvgOptions, source_ids: &[usize]) -> String {
+    svg_from_matrix_with_options(&with_source_ids(matrix, source_ids), style)
+}
+
+fn with_source_ids(matrix: &DMatrix<u32>, source_ids: &[usize]) -> DMatrix<u32> {
+    matrix.map(|cell| {
+        piece_index(cell)
+            .and_then(|id| source_ids.get(id))
+            .map_or(cell, |&source_id| with_piece_index(cell, source_id))
+    })
+}
+
+fn solution_array(game: &Game, solutions: &[DMatrix<u32>]) -> JSSolutionArray {
+    solutions.iter()
+    .map(|matrix| JSSolution::new(game, matrix))
+    .map(JsValue::from)
+    .collect::<js_sys::Array>()
+    .unchecked_into::<JSSolutionArray>()
+}
+
 
 
 #[wasm_bindgen(getter_with_clone)]
 pub struct JSGame {
     #[wasm_bindgen(skip)]
     pub game: Game,
+    #[wasm_bindgen(skip)]
+    pub style: SvgOptions,
+    /// Handles to the pieces, built on first use and cleared when the pieces or the style change.
+    pieces: RefCell<Vec<JSPiece>>,
+    /// Id of each piece in the game it was picked from with `game_from_game`, used as `data-piece-id`.
+    source_ids: Rc<[usize]>,
 }
 
 #[wasm_bindgen]
@@ -69,35 +374,125 @@ impl JSGame {
     }
 
     pub fn piece(&self, id: usize) -> JSPiece {
-        let piece = self.game.piece(id).expect("Invalid piece ID");
-        JSPiece::new(id, piece)
+        self.cached_pieces().get(id).expect("Invalid piece ID").clone()
     }
 
     #[wasm_bindgen(getter)]
     pub fn pieces(&self) -> JSPieceArray {
-        self.game.piece_ids().into_iter()
-        .map(|id| {
-            let piece = self.game.piece(id).expect("Invalid piece ID");
-            JSPiece::new(id, piece)
-        })
+        self.cached_pieces().iter()
+        .cloned()
         .map(JsValue::from)
         .collect::<js_sys::Array>()
         .unchecked_into::<JSPieceArray>()
     }
+
+    /// Recolors the pieces with one of the palettes listed by `palette_names`.
+    pub fn set_palette(&mut self, name: &str) -> Result<(), JsError> {
+        let palette = Palette::from_name(name)
+            .ok_or_else(|| JsError::new(&format!("Unknown palette: {name}")))?;
+        palette.apply(&mut self.game);
+        self.pieces.get_mut().clear();
+        Ok(())
+    }
+
+    /// Selects the pattern fills and piece letters drawn on the rendered SVGs.
+    pub fn set_svg_options(&mut self, patterns: bool, letters: bool) {
+        self.style = SvgOptions { patterns, letters };
+        self.pieces.get_mut().clear();
+    }
+
+    /// Checks a board assembled by the player.
+    ///
+    /// `cells` holds the `JSPiece.id` of the piece covering each cell, row by row,
+    /// and `empty_cell_id()` for empty cells. Throws an error describing the first
+    /// empty cell, unknown or missing piece, or misshapen piece found.
+    pub fn verify(&self, cells: &[u8]) -> Result<(), JsError> {
+        let solution = grid_to_matrix(&self.game, cells)?;
+        self.game.verify(&solution).map_err(|error| JsError::new(&error.to_string()))
+    }
+
+    /// Explains why the game has no solution, or returns `undefined` when it has one.
+    ///
+    /// Quick checks come first, so this is often much faster than searching for the solutions;
+    /// otherwise it searches for one solution. Throws an error when the pieces do not fill the board.
+    pub fn explain_no_solution(&self) -> Result<Option<JSDeadEnd>, JsError> {
+        self.game.validate().map_err(|error| JsError::new(&error.to_string()))?;
+        let dead_end = dead_end::explain(&self.game, 0, &self.game.piece_ids());
+        Ok(dead_end.map(|dead_end| JSDeadEnd::new(dead_end, self.game.columns as usize)))
+    }
+
+    /// Renders a piece-id grid, such as `JSSolution.grid`, as an SVG document.
+    ///
+    /// Empty cells are left transparent, so partially filled boards can be rendered too.
+    pub fn render_grid(&self, cells: &[u8]) -> Result<String, JsError> {
+        Ok(render_board(&grid_to_matrix(&self.game, cells)?, &self.style, &self.source_ids))
+    }
+
+    /// Returns the value of the empty cells in piece-id grids.
+    pub fn empty_cell_id() -> u8 {
+        EMPTY_CELL_ID
+    }
+
+    /// Returns the names of the available color palettes.
+    pub fn palette_names() -> Vec<String> {
+        Palette::ALL.iter().map(|palette| palette.name().to_owned()).collect()
+    }
+}
+
+impl JSGame {
+    pub fn new_with_style(game: Game, style: SvgOptions) -> Self {
+        let source_ids = (0..game.pieces.len()).collect();
+        Self { game, style, pieces: RefCell::new(Vec::new()), source_ids }
+    }
+
+    /// Returns the handles to the pieces, building them if needed.
+    fn cached_pieces(&self) -> Ref<'_, Vec<JSPiece>> {
+        if self.pieces.borrow().len() != self.game.pieces.len() {
+            *self.pieces.borrow_mut() = self.game.pieces.iter()
+                .enumerate()
+                .map(|(id, piece)| JSPiece::with_source_id(id, self.source_ids[id], piece, &self.style))
+                .collect();
+        }
+        self.pieces.borrow()
+    }
 }
 
 #[wasm_bindgen]
 impl JSGame {
     pub fn game_with_all_pieces() -> JSGame {
-        Self {
-            game: Game::game_with_all_pieces()
+        Self::new_with_style(Game::game_with_all_pieces(), SvgOptions::default())
+    }
+
+    /// Creates a game of custom pieces on a board of `columns` columns.
+    ///
+    /// The pieces are moved into the game: get them back, with their ids, from `pieces`.
+    /// Throws an error when the game cannot be solved, for example when the pieces do not fill complete rows.
+    #[wasm_bindgen(constructor)]
+    pub fn new(pieces: Vec<JSPiece>, columns: u32) -> Result<JSGame, JsError> {
+        let game = Game { columns, pieces: pieces.iter().map(|piece| piece.piece().clone()).collect() };
+        game.validate().map_err(|error| JsError::new(&error.to_string()))?;
+        Ok(Self::new_with_style(game, SvgOptions::default()))
+    }
+
+    /// Creates a game of custom pieces on a board of `columns` by `rows` cells.
+    ///
+    /// Same as the constructor, also checking that the pieces cover exactly the board.
+    pub fn with_board(pieces: Vec<JSPiece>, columns: u32, rows: u32) -> Result<JSGame, JsError> {
+        let cells: u64 = pieces.iter().map(|piece| u64::from(piece.cells())).sum();
+        let board = u64::from(columns) * u64::from(rows);
+        if cells != board {
+            return Err(JsError::new(&format!("The pieces cover {cells} cells, the {columns}x{rows} board has {board}")));
         }
+        Self::new(pieces, columns)
     }
 
+    /// Creates the game of the pieces of `game` at `piece_ids`.
+    ///
+    /// The pieces keep their ids of `game` as `JSPiece.source_id` and as `data-piece-id` in the SVGs,
+    /// so that a piece on a board can be linked to the piece list of `game`.
     pub fn game_from_game(game: &JSGame, piece_ids: Vec<usize>) -> Self {
-        Self {
-            game: Game::game_from_game(&game.game, piece_ids)
-        }
+        let source_ids = piece_ids.iter().map(|&id| game.source_ids.get(id).copied().unwrap_or(id)).collect();
+        Self { source_ids, ..Self::new_with_style(Game::game_from_game(&game.game, piece_ids), game.style) }
     }
 }
 
@@ -106,7 +501,7 @@ impl JSGame {
     pub fn resolve(&self) -> JSMatrixArray {
         let resolver = GameResolver {};
         resolver.resolve(&self.game).iter()
-        .map(JSMatrix::new)
+        .map(|matrix| JSMatrix::new(&with_source_ids(matrix, &self.source_ids), &self.style))
         .map(JsValue::from)
         .collect::<js_sys::Array>()
         .unchecked_into::<JSMatrixArray>()
@@ -115,13 +510,474 @@ impl JSGame {
     pub fn resolve_page(&self, page_index: usize, page_size: usize) -> JSMatrixArray {
         let resolver = GameResolver {};
         resolver.resolve_page(&self.game, page_index, page_size).iter()
-        .map(JSMatrix::new)
+        .map(|matrix| JSMatrix::new(&with_source_ids(matrix, &self.source_ids), &self.style))
         .map(JsValue::from)
         .collect::<js_sys::Array>()
         .unchecked_into::<JSMatrixArray>()
     }
+
+    /// Returns a page of solutions as typed data, without rendering them.
+    pub fn resolve_page_data(&self, page_index: usize, page_size: usize) -> JSSolutionArray {
+        let resolver = GameResolver {};
+        solution_array(&self.game, &resolver.resolve_page(&self.game, page_index, page_size))
+    }
+
+    /// Returns all the solutions packed into one buffer, to be rendered on demand.
+    pub fn resolve_packed(&self) -> JSSolutionBatch {
+        let resolver = GameResolver {};
+        JSSolutionBatch::new(self, &resolver.resolve(&self.game))
+    }
+
+    /// Returns a page of solutions packed into one buffer.
+    pub fn resolve_page_packed(&self, page_index: usize, page_size: usize) -> JSSolutionBatch {
+        let resolver = GameResolver {};
+        JSSolutionBatch::new(self, &resolver.resolve_page(&self.game, page_index, page_size))
+    }
+
+    /// Renders a page of solutions as a single contact-sheet SVG document.
+    ///
+    /// This avoids creating one blob URL per `JSMatrix` when displaying many solutions.
+    pub fn resolve_contact_sheet(&self, page_index: usize, page_size: usize, columns: usize, gap: usize, labels: bool) -> String {
+        let resolver = GameResolver {};
+        let solutions: Vec<_> = resolver.resolve_page(&self.game, page_index, page_size)
+            .iter()
+            .map(|solution| with_source_ids(solution, &self.source_ids))
+            .collect();
+        let options = ContactSheetOptions {
+            columns,
+            gap,
+            labels,
+            first_label: page_index.saturating_mul(page_size).saturating_add(1),
+            style: self.style,
+        };
+        svg_contact_sheet(&solutions, &options)
+    }
+}
+
+
+/// Number of placements tried between two clock reads in `JSSolverSession::step`.
+const PLACEMENTS_PER_CLOCK_READ: usize = 2_000;
+
+/// A search for the solutions of a game, run in time slices so that it never blocks the page for long.
+///
+/// Call `step` repeatedly, for example from `requestAnimationFrame` or in a Web Worker loop,
+/// until `done` is set. `cancel` stops the search; the session can then be dropped with `free`.
+#[wasm_bindgen]
+pub struct JSSolverSession {
+    session: SolverSession,
+    game: Game,
+    cancelled: bool,
+}
+
+#[wasm_bindgen]
+impl JSSolverSession {
+    /// Starts a search for the solutions of `game`.
+    ///
+    /// Throws an error when the game cannot be solved, for example when the pieces do not fill complete rows.
+    #[wasm_bindgen(constructor)]
+    pub fn new(game: &JSGame) -> Result<JSSolverSession, JsError> {
+        game.game.validate().map_err(|error| JsError::new(&error.to_string()))?;
+        Ok(Self {
+            session: SolverSession::new(&game.game),
+            game: Game { columns: game.game.columns, pieces: game.game.pieces.clone() },
+            cancelled: false,
+        })
+    }
+
+    /// Searches for about `budget_ms` milliseconds, returning the solutions found meanwhile.
+    ///
+    /// Solutions are typed data; render them with `JSGame.render_grid` when they are shown.
+    pub fn step(&mut self, budget_ms: f64) -> JSSolutionArray {
+        let deadline = js_sys::Date::now() + budget_ms;
+        let mut solutions = Vec::new();
+        while !self.done() {
+            solutions.extend(self.session.step(PLACEMENTS_PER_CLOCK_READ));
+            if js_sys::Date::now() >= deadline {
+                break;
+            }
+        }
+        solution_array(&self.game, &solutions)
+    }
+
+    /// Stops the search: later calls to `step` return no solutions.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    /// Whether the search is over, because all solutions were found or it was cancelled.
+    #[wasm_bindgen(getter)]
+    pub fn done(&self) -> bool {
+        self.cancelled || self.session.is_done()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Estimated part of the search already done, from 0 to 1.
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> f64 {
+        self.session.progress()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solutions_found(&self) -> usize {
+        self.session.solutions_found()
+    }
+
+    /// Number of piece placements tried so far, as a measure of the work done.
+    #[wasm_bindgen(getter)]
+    pub fn placements_tried(&self) -> f64 {
+        self.session.placements_tried() as f64
+    }
+}
+
+
+/// Kind of change made to the board by an action of a `JSPlaySession`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JSPlayEventKind {
+    Placed,
+    /// The piece was taken off the board, and is now held.
+    Removed,
+    /// The last piece was put on the board, which is now a solution.
+    Completed,
+}
+
+/// A change made to the board, and the placement of the piece that was put or taken off.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct JSPlayEvent {
+    pub kind: JSPlayEventKind,
+    pub placement: JSPlacement,
+}
+
+impl From<PlayEvent> for JSPlayEvent {
+    fn from(event: PlayEvent) -> Self {
+        let (kind, placement) = match event {
+            PlayEvent::Placed(placement) => (JSPlayEventKind::Placed, placement),
+            PlayEvent::Removed(placement) => (JSPlayEventKind::Removed, placement),
+            PlayEvent::Completed(placement) => (JSPlayEventKind::Completed, placement),
+        };
+        Self { kind, placement: placement.into() }
+    }
+}
+
+/// Kind of reason why a board cannot be completed, see `JSDeadEnd`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JSDeadEndKind {
+    /// The piece `piece_id` is longer or wider than the board in every orientation.
+    PieceTooLarge,
+    /// The piece `piece_id` fits nowhere in the free cells.
+    NoRoomForPiece,
+    /// The free `cells`, closed off from the rest of the board, cannot be filled by the pieces left.
+    IsolatedRegion,
+    /// The pieces left cannot cover the `dark` and `light` free cells of a checkerboard.
+    CheckerboardParity,
+    /// Searching every arrangement of the pieces found no solution.
+    NoArrangement,
+}
+
+/// Why a board cannot be completed, as data for the web app to word in the language of the player.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct JSDeadEnd {
+    kind: JSDeadEndKind,
+    piece_id: Option<usize>,
+    cells: Vec<usize>,
+    dark: u32,
+    light: u32,
+    message: String,
+}
+
+impl JSDeadEnd {
+    fn new(dead_end: DeadEnd, columns: usize) -> Self {
+        let message = dead_end.to_string();
+        let reason_of = |kind| Self { kind, piece_id: None, cells: Vec::new(), dark: 0, light: 0, message: message.clone() };
+        match dead_end {
+            DeadEnd::PieceTooLarge { piece } => Self { piece_id: Some(piece), ..reason_of(JSDeadEndKind::PieceTooLarge) },
+            DeadEnd::NoRoomForPiece { piece } => Self { piece_id: Some(piece), ..reason_of(JSDeadEndKind::NoRoomForPiece) },
+            DeadEnd::IsolatedRegion { cells } => Self {
+                cells: cells.into_iter().map(|(row, col)| row * columns + col).collect(),
+                ..reason_of(JSDeadEndKind::IsolatedRegion)
+            },
+            DeadEnd::CheckerboardParity { dark, light } => Self { dark, light, ..reason_of(JSDeadEndKind::CheckerboardParity) },
+            DeadEnd::NoArrangement => reason_of(JSDeadEndKind::NoArrangement),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl JSDeadEnd {
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> JSDeadEndKind {
+        self.kind
+    }
+
+    /// `JSPiece.id` of the piece at fault, for the `PieceTooLarge` and `NoRoomForPiece` kinds.
+    #[wasm_bindgen(getter)]
+    pub fn piece_id(&self) -> Option<usize> {
+        self.piece_id
+    }
+
+    /// Indices of the cells of the region, `row * width + col`, for the `IsolatedRegion` kind.
+    #[wasm_bindgen(getter)]
+    pub fn cells(&self) -> Vec<usize> {
+        self.cells.clone()
+    }
+
+    /// Number of free cells on the dark squares of a checkerboard, whose top left square is dark, for the `CheckerboardParity` kind.
+    #[wasm_bindgen(getter)]
+    pub fn dark(&self) -> u32 {
+        self.dark
+    }
+
+    /// Number of free cells on the light squares of a checkerboard, for the `CheckerboardParity` kind.
+    #[wasm_bindgen(getter)]
+    pub fn light(&self) -> u32 {
+        self.light
+    }
+
+    /// The reason in English, for logs and debugging.
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+/// How much `JSPlaySession.hint` gives away, from the least to the most.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JSHintLevel {
+    Solvable,
+    Piece,
+    Placement,
+}
+
+impl From<JSHintLevel> for HintLevel {
+    fn from(level: JSHintLevel) -> Self {
+        match level {
+            JSHintLevel::Solvable => Self::Solvable,
+            JSHintLevel::Piece => Self::Piece,
+            JSHintLevel::Placement => Self::Placement,
+        }
+    }
+}
+
+/// Kind of answer of `JSPlaySession.hint`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JSHintKind {
+    /// Every piece is on the board.
+    Complete,
+    /// The board can still be completed.
+    Solvable,
+    /// The board can still be completed by placing `piece_id` next.
+    Piece,
+    /// The board can still be completed by placing a piece at `placement` next.
+    Placement,
+    /// The board cannot be completed, for `reason`; `blocking` lists the pieces to take off.
+    Unsolvable,
+}
+
+/// A hint for the board of a `JSPlaySession`.
+#[wasm_bindgen]
+pub struct JSHint {
+    kind: JSHintKind,
+    placement: Option<JSPlacement>,
+    piece_id: Option<usize>,
+    blocking: Vec<usize>,
+    reason: Option<JSDeadEnd>,
+}
+
+impl JSHint {
+    fn new(hint: Hint, columns: usize) -> Self {
+        let hint_of = |kind| Self { kind, placement: None, piece_id: None, blocking: Vec::new(), reason: None };
+        match hint {
+            Hint::Complete => hint_of(JSHintKind::Complete),
+            Hint::Solvable => hint_of(JSHintKind::Solvable),
+            Hint::Piece(piece) => Self { piece_id: Some(piece), ..hint_of(JSHintKind::Piece) },
+            Hint::Placement(placement) => Self {
+                placement: Some(placement.into()),
+                piece_id: Some(placement.piece),
+                ..hint_of(JSHintKind::Placement)
+            },
+            Hint::Unsolvable { reason, blocking } => Self {
+                blocking,
+                reason: Some(JSDeadEnd::new(reason, columns)),
+                ..hint_of(JSHintKind::Unsolvable)
+            },
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl JSHint {
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> JSHintKind {
+        self.kind
+    }
+
+    /// `JSPiece.id` of the piece to place next, for the `Piece` and `Placement` kinds.
+    #[wasm_bindgen(getter)]
+    pub fn piece_id(&self) -> Option<usize> {
+        self.piece_id
+    }
+
+    /// Where to place the next piece, for the `Placement` kind.
+    #[wasm_bindgen(getter)]
+    pub fn placement(&self) -> Option<JSPlacement> {
+        self.placement
+    }
+
+    /// `JSPiece.id` of the placed pieces that, taken off on their own, make the board solvable again.
+    ///
+    /// Empty unless the kind is `Unsolvable`, and also when no single piece is to blame.
+    #[wasm_bindgen(getter)]
+    pub fn blocking(&self) -> Vec<usize> {
+        self.blocking.clone()
+    }
+
+    /// Why the board cannot be completed, for the `Unsolvable` kind.
+    #[wasm_bindgen(getter)]
+    pub fn reason(&self) -> Option<JSDeadEnd> {
+        self.reason.clone()
+    }
+}
+
+fn play_error(error: PlayError) -> JsError {
+    JsError::new(&error.to_string())
 }
 
+/// A game being played: pieces put on the board and taken back by the player, with undo and redo.
+///
+/// Orientations are indices among the distinct rotations and reflections of a piece, as in `JSPlacement`.
+#[wasm_bindgen]
+pub struct JSPlaySession {
+    session: PlaySession,
+    style: SvgOptions,
+    source_ids: Rc<[usize]>,
+}
+
+#[wasm_bindgen]
+impl JSPlaySession {
+    /// Starts playing `game` on an empty board, with its current palette and SVG options.
+    #[wasm_bindgen(constructor)]
+    pub fn new(game: &JSGame) -> Result<JSPlaySession, JsError> {
+        let copy = Game { columns: game.game.columns, pieces: game.game.pieces.clone() };
+        let session = PlaySession::new(copy).map_err(|error| JsError::new(&error.to_string()))?;
+        Ok(Self { session, style: game.style, source_ids: Rc::clone(&game.source_ids) })
+    }
+
+    /// Number of orientations of a piece, the valid orientations being `0..count`.
+    pub fn orientation_count(&self, piece_id: usize) -> usize {
+        self.session.orientations(piece_id).map_or(0, <[DMatrix<u32>]>::len)
+    }
+
+    /// Takes a piece not on the board in hand, in `orientation`.
+    pub fn hold(&mut self, piece_id: usize, orientation: usize) -> Result<(), JsError> {
+        self.session.hold(piece_id, orientation).map_err(play_error)
+    }
+
+    pub fn release(&mut self) {
+        self.session.release();
+    }
+
+    /// Turns the held piece a quarter turn clockwise, returning its new orientation.
+    pub fn rotate(&mut self) -> Result<usize, JsError> {
+        self.session.rotate().map_err(play_error)
+    }
+
+    /// Turns the held piece over, left to right, returning its new orientation.
+    pub fn flip(&mut self) -> Result<usize, JsError> {
+        self.session.flip().map_err(play_error)
+    }
+
+    /// Puts a piece on the board with its top left corner at `row` and `col`.
+    ///
+    /// Throws an error when the piece sticks out of the board, overlaps another piece, or is already placed.
+    pub fn place(&mut self, piece_id: usize, orientation: usize, row: usize, col: usize) -> Result<JSPlayEvent, JsError> {
+        self.session.place(piece_id, orientation, row, col).map(JSPlayEvent::from).map_err(play_error)
+    }
+
+    /// Puts the held piece on the board, in its orientation.
+    pub fn place_held(&mut self, row: usize, col: usize) -> Result<JSPlayEvent, JsError> {
+        self.session.place_held(row, col).map(JSPlayEvent::from).map_err(play_error)
+    }
+
+    /// Takes a piece off the board and holds it.
+    pub fn remove(&mut self, piece_id: usize) -> Result<JSPlayEvent, JsError> {
+        self.session.remove(piece_id).map(JSPlayEvent::from).map_err(play_error)
+    }
+
+    pub fn undo(&mut self) -> Result<JSPlayEvent, JsError> {
+        self.session.undo().map(JSPlayEvent::from).map_err(play_error)
+    }
+
+    pub fn redo(&mut self) -> Result<JSPlayEvent, JsError> {
+        self.session.redo().map(JSPlayEvent::from).map_err(play_error)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_undo(&self) -> bool {
+        self.session.can_undo()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_redo(&self) -> bool {
+        self.session.can_redo()
+    }
+
+    /// Whether every piece is on the board, which is then a solution.
+    #[wasm_bindgen(getter)]
+    pub fn complete(&self) -> bool {
+        self.session.is_complete()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn held_piece(&self) -> Option<usize> {
+        self.session.held().map(|(piece, _)| piece)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn held_orientation(&self) -> Option<usize> {
+        self.session.held().map(|(_, orientation)| orientation)
+    }
+
+    /// `JSPiece.id` of the pieces not on the board.
+    #[wasm_bindgen(getter)]
+    pub fn remaining(&self) -> Vec<usize> {
+        self.session.remaining()
+    }
+
+    /// Piece-id grid of the board, as in `JSSolution.grid`, with `JSGame.empty_cell_id()` for empty cells.
+    #[wasm_bindgen(getter)]
+    pub fn grid(&self) -> Vec<u8> {
+        grid_cells(&self.session.board()).collect()
+    }
+
+    /// Placements of the pieces on the board, in the order they were placed.
+    #[wasm_bindgen(getter)]
+    pub fn placements(&self) -> JSPlacementArray {
+        self.session.placements().into_iter()
+        .map(JSPlacement::from)
+        .map(JsValue::from)
+        .collect::<js_sys::Array>()
+        .unchecked_into::<JSPlacementArray>()
+    }
+
+    /// Tells whether the board can still be completed, and with `level`, which piece to place next and where.
+    pub fn hint(&self, level: JSHintLevel) -> JSHint {
+        JSHint::new(self.session.hint(level.into()), self.session.columns())
+    }
+
+    /// Renders the pieces on the board.
+    pub fn svg(&self) -> String {
+        render_board(&self.session.board(), &self.style, &self.source_ids)
+    }
+}
 
 
 #[wasm_bindgen]
@@ -134,4 +990,38 @@ extern "C" {
 
     #[wasm_bindgen(typescript_type = "Array<JSPiece>")]
     pub type JSPieceArray;
+
+    #[wasm_bindgen(typescript_type = "Array<JSSolution>")]
+    pub type JSSolutionArray;
+
+    #[wasm_bindgen(typescript_type = "Array<JSPlacement>")]
+    pub type JSPlacementArray;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_from_game_keeps_source_piece_ids() {
+        let all = JSGame::game_with_all_pieces();
+        // Orange L, brown L and tan bar, on a 2x5 board
+        let game = JSGame::game_from_game(&all, vec![11, 4, 2]);
+        let solution = GameResolver.resolve_page(&game.game, 0, 1).pop().expect("The game has a solution");
+
+        let svg = render_board(&solution, &game.style, &game.source_ids);
+        for id in [11, 4, 2] {
+            assert!(svg.contains(&format!("data-piece-id=\"{id}\"")), "piece {id} in {svg}");
+        }
+        assert!(!svg.contains("data-piece-id=\"0\""));
+
+        let pieces = game.cached_pieces();
+        assert_eq!(pieces[0].id(), 0);
+        assert_eq!(pieces[0].source_id(), 11);
+        assert!(pieces[0].svg().contains("data-piece-id=\"11\""));
+
+        // Picking again from the subset still refers to the pieces of the full game
+        let subset = JSGame::game_from_game(&game, vec![1]);
+        assert_eq!(&*subset.source_ids, &[4]);
+    }
 }
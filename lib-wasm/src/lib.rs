@@ -4,6 +4,7 @@ use wasm_bindgen::prelude::*;
 
 use gtp_lib::models::*;
 use gtp_lib::game_data::*;
+use gtp_lib::svg_renderer::SvgOptions;
 use gtp_lib::{GameResolver, GameResolverTrait};
 
 use js_models::*;
@@ -22,9 +23,7 @@ impl JSGame {
             PieceName::OrangeL5.piece(),
         ];
 
-        Self {
-            game: Game { columns: 5, pieces }
-        }
+        Self::new_with_style(Game { columns: 5, pieces }, SvgOptions::default())
     }
 
     // Returns only the count of solutions for the game.
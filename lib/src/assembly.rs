@@ -0,0 +1,103 @@
+use nalgebra::DMatrix;
+use std::cmp::Reverse;
+
+use crate::game_resolver::piece_index;
+
+/// Order in which the pieces of a solution are put on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssemblyOrder {
+    /// The order used by [`crate::GameResolver`]: decreasing size, then piece index in the game.
+    #[default]
+    Solver,
+    /// Decreasing size, ties broken by the position of the piece on the board.
+    LargestFirst,
+    /// Reading order: the piece whose first cell comes first, row by row, goes first.
+    TopToBottom,
+}
+
+/// A piece as it appears in a solution matrix.
+struct PlacedPiece {
+    /// Cell value shared by all the cells of the piece.
+    value: u32,
+    /// Number of cells covered.
+    cells: usize,
+    /// Row-major index of the first cell of the piece.
+    first_cell: usize,
+}
+
+/// Returns the cell values of the pieces of `matrix`, in assembly order.
+///
+/// Each piece is identified by its cell value (color plus encoded piece id),
+/// so every cell holding that value belongs to the returned step.
+#[must_use]
+pub fn assembly_steps(matrix: &DMatrix<u32>, order: AssemblyOrder) -> Vec<u32> {
+    let mut pieces: Vec<PlacedPiece> = Vec::new();
+    for row in 0..matrix.nrows() {
+        for col in 0..matrix.ncols() {
+            let value = matrix[(row, col)];
+            if value == 0 {
+                continue;
+            }
+            match pieces.iter_mut().find(|piece| piece.value == value) {
+                Some(piece) => piece.cells += 1,
+                None => pieces.push(PlacedPiece {
+                    value,
+                    cells: 1,
+                    first_cell: row * matrix.ncols() + col,
+                }),
+            }
+        }
+    }
+
+    match order {
+        AssemblyOrder::Solver => pieces.sort_by_key(|piece| (Reverse(piece.cells), piece_index(piece.value), piece.value)),
+        AssemblyOrder::LargestFirst => pieces.sort_by_key(|piece| (Reverse(piece.cells), piece.first_cell)),
+        AssemblyOrder::TopToBottom => pieces.sort_by_key(|piece| piece.first_cell),
+    }
+
+    pieces.into_iter().map(|piece| piece.value).collect()
+}
+
+/// Builds the static frames of the assembly of `matrix`.
+///
+/// Frame `i` contains the first `i + 1` pieces in `order`, the last frame being the full solution.
+/// Frames can be displayed in the terminal or rendered like any other board matrix.
+#[must_use]
+pub fn assembly_frames(matrix: &DMatrix<u32>, order: AssemblyOrder) -> Vec<DMatrix<u32>> {
+    let mut frame = DMatrix::<u32>::zeros(matrix.nrows(), matrix.ncols());
+    assembly_steps(matrix, order)
+        .into_iter()
+        .map(|value| {
+            frame += matrix.map(|cell| if cell == value { cell } else { 0 });
+            frame.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Piece 0 (1 cell) at the top left, piece 1 (3 cells) around it.
+    const SMALL: u32 = 0x0100_0001;
+    const LARGE: u32 = 0x0200_0002;
+
+    fn solution() -> DMatrix<u32> {
+        DMatrix::from_row_slice(2, 2, &[SMALL, LARGE, LARGE, LARGE])
+    }
+
+    #[test]
+    fn test_assembly_steps() {
+        assert_eq!(assembly_steps(&solution(), AssemblyOrder::Solver), vec![LARGE, SMALL]);
+        assert_eq!(assembly_steps(&solution(), AssemblyOrder::LargestFirst), vec![LARGE, SMALL]);
+        assert_eq!(assembly_steps(&solution(), AssemblyOrder::TopToBottom), vec![SMALL, LARGE]);
+    }
+
+    #[test]
+    fn test_assembly_frames() {
+        let frames = assembly_frames(&solution(), AssemblyOrder::Solver);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0], DMatrix::from_row_slice(2, 2, &[0, LARGE, LARGE, LARGE]));
+        assert_eq!(frames[1], solution());
+    }
+}
@@ -1,6 +1,6 @@
 // Bitboard utilities for Gagne Ton Papa solver
-// Supports boards up to 11x11 (128 bits) which is sufficient for current puzzles.
-// The board is stored as a u128 where bit i corresponds to cell (row, col)
+// Supports boards up to 64 cells (see `MAX_BOARD_CELLS`) which is sufficient for current puzzles.
+// The board is stored as a u64 where bit i corresponds to cell (row, col)
 // i = row * board_cols + col (row-major order).
 
 use nalgebra::DMatrix;
@@ -8,6 +8,9 @@ use nalgebra::DMatrix;
 pub type BitBoard = u64;
 
 /// Convert a piece matrix (with its color already multiplied) into a `BitBoard` positioned at (`offset_row`, `offset_col`).
+///
+/// The piece must fit on the board at that position, see [`fits`].
+#[must_use]
 pub fn matrix_to_bitboard(
     matrix: &DMatrix<u32>,
     _board_rows: usize,
@@ -31,7 +34,7 @@ pub fn matrix_to_bitboard(
 }
 
 /// Convert a `BitBoard` back into a `DMatrix`<u32> with the given dimensions.
-#[allow(dead_code)]
+#[must_use]
 pub fn bitboard_to_matrix(bits: BitBoard, rows: usize, cols: usize) -> DMatrix<u32> {
     let mut mat = DMatrix::<u32>::zeros(rows, cols);
     for idx in 0..(rows * cols) {
@@ -44,9 +47,22 @@ pub fn bitboard_to_matrix(bits: BitBoard, rows: usize, cols: usize) -> DMatrix<u
     mat
 }
 
+/// Returns whether a piece matrix placed at (`offset_row`, `offset_col`) stays within the board.
+#[must_use]
+pub fn fits(matrix: &DMatrix<u32>, board_rows: usize, board_cols: usize, offset_row: usize, offset_col: usize) -> bool {
+    offset_row + matrix.nrows() <= board_rows && offset_col + matrix.ncols() <= board_cols
+}
+
+/// Returns whether a placement overlaps the cells already occupied on the board.
+#[must_use]
+pub const fn collides(board: BitBoard, placement: BitBoard) -> bool {
+    board & placement != 0
+}
+
 /// Generate all possible placements for a piece on a board.
 /// Returns a vector of tuples `(placement_bitboard, placed_matrix)`.
 /// `placed_matrix` has the piece's color applied and is padded to board size.
+#[must_use]
 pub fn generate_positions(
     piece: &crate::models::Piece,
     board_rows: usize,
@@ -126,6 +142,17 @@ mod tests {
         assert_eq!(matrix.ncols(), 3);
     }
 
+    #[test]
+    fn test_fits_and_collides() {
+        let matrix = create_matrix(1, 2, &[1, 1]);
+        assert!(fits(&matrix, 3, 3, 2, 1));
+        assert!(!fits(&matrix, 3, 3, 2, 2));
+
+        let board = matrix_to_bitboard(&matrix, 3, 3, 1, 1);
+        assert!(collides(board, matrix_to_bitboard(&matrix, 3, 3, 1, 0)));
+        assert!(!collides(board, matrix_to_bitboard(&matrix, 3, 3, 2, 0)));
+    }
+
     #[test]
     fn test_generate_positions() {
         // 2x2 board
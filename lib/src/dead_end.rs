@@ -0,0 +1,248 @@
+//! Explanations of why a board cannot be completed, as structured data for the user interfaces to word.
+
+use std::fmt;
+
+use nalgebra::DMatrix;
+
+use crate::bitboard::{generate_positions, BitBoard};
+use crate::game_resolver::GameResolver;
+use crate::matrix_tools::rotation_variants;
+use crate::models::{Game, Piece};
+
+/// Why a board, empty or partially filled, cannot be completed.
+///
+/// Pieces are given as their index in [`Game::pieces`], and cells as `(row, column)`, starting at 0.
+/// Rows and columns are displayed starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeadEnd {
+    /// A piece is longer or wider than the board in every orientation.
+    PieceTooLarge { piece: usize },
+    /// A piece left to place fits nowhere in the free cells.
+    NoRoomForPiece { piece: usize },
+    /// A region of free cells, closed off by the placed pieces and the edges of the board,
+    /// that no group of the pieces left can fill exactly.
+    IsolatedRegion { cells: Vec<(usize, usize)> },
+    /// Coloring the board like a checkerboard, with a dark top left cell, the pieces left
+    /// cannot cover the `dark` and `light` free cells: each piece covers a fixed difference
+    /// between dark and light cells wherever it goes.
+    CheckerboardParity { dark: u32, light: u32 },
+    /// None of the above, but searching every arrangement of the pieces found no solution.
+    NoArrangement,
+}
+
+impl fmt::Display for DeadEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PieceTooLarge { piece } => write!(f, "piece {piece} is too large for the board"),
+            Self::NoRoomForPiece { piece } => write!(f, "piece {piece} fits nowhere in the free cells"),
+            Self::IsolatedRegion { cells } => {
+                let cells: Vec<String> = cells.iter().map(|(row, col)| format!("({}, {})", row + 1, col + 1)).collect();
+                write!(f, "the pieces left cannot fill the free cells at {}", cells.join(", "))
+            }
+            Self::CheckerboardParity { dark, light } => {
+                write!(f, "the pieces left cannot cover the {dark} dark and {light} light free cells of a checkerboard")
+            }
+            Self::NoArrangement => write!(f, "no arrangement of the pieces fills the board"),
+        }
+    }
+}
+
+/// Returns why the `remaining` pieces cannot be placed around the `occupied` cells of the board
+/// of `game`, or `None` when they can.
+///
+/// `occupied` and `remaining` are given as for [`GameResolver::resolve_from_bitboard`]; an empty
+/// board and all the pieces explain why the game itself has no solution. The quick checks of
+/// [`explain_unsolvable`] come first, and the search only runs when they find nothing.
+///
+/// `game` must be valid, see [`Game::validate`].
+///
+/// # Panics
+///
+/// Panics if the board exceeds 64 cells.
+#[must_use]
+pub fn explain(game: &Game, occupied: BitBoard, remaining: &[usize]) -> Option<DeadEnd> {
+    quick_check(game, occupied, remaining).or_else(|| {
+        GameResolver.resolve_from_bitboard(game, occupied, remaining, 1).is_empty().then_some(DeadEnd::NoArrangement)
+    })
+}
+
+/// Returns why a board already known to have no completion cannot be completed, without searching again.
+///
+/// Falls back to [`DeadEnd::NoArrangement`] when no quick check explains it.
+///
+/// # Panics
+///
+/// Panics if the board exceeds 64 cells.
+#[must_use]
+pub fn explain_unsolvable(game: &Game, occupied: BitBoard, remaining: &[usize]) -> DeadEnd {
+    quick_check(game, occupied, remaining).unwrap_or(DeadEnd::NoArrangement)
+}
+
+/// Runs the checks cheaper than a search, from the simplest reason to the least obvious one.
+fn quick_check(game: &Game, occupied: BitBoard, remaining: &[usize]) -> Option<DeadEnd> {
+    let rows = game.rows() as usize;
+    let cols = game.columns as usize;
+    let free = !occupied & board_mask(rows * cols);
+
+    for &piece in remaining {
+        let mut positions = rotation_variants(&game.pieces[piece].matrix)
+            .into_iter()
+            .flat_map(|matrix| generate_positions(&Piece { matrix, color: 1, tui_color: 0 }, rows, cols))
+            .peekable();
+        if positions.peek().is_none() {
+            return Some(DeadEnd::PieceTooLarge { piece });
+        }
+        if !positions.any(|(bits, _)| bits & !free == 0) {
+            return Some(DeadEnd::NoRoomForPiece { piece });
+        }
+    }
+
+    let sizes: Vec<u32> = remaining.iter().map(|&piece| game.pieces[piece].cells()).collect();
+    let fillable = subset_sums(&sizes);
+    for region in regions(free, cols) {
+        if !fillable.get(region.len()).copied().unwrap_or(false) {
+            return Some(DeadEnd::IsolatedRegion { cells: region.into_iter().map(|cell| (cell / cols, cell % cols)).collect() });
+        }
+    }
+
+    // Each piece covers `imbalance` more cells of one color than of the other, and the placement decides which color
+    let (dark, light) = (0..rows * cols)
+        .filter(|&cell| free & (1 << cell) != 0)
+        .fold((0, 0), |(dark, light), cell| if is_dark(cell / cols, cell % cols) { (dark + 1, light) } else { (dark, light + 1) });
+    let imbalances: Vec<u32> = remaining.iter().map(|&piece| imbalance(&game.pieces[piece].matrix)).collect();
+    let total: u32 = imbalances.iter().sum();
+    // Some pieces add their imbalance to the dark cells and the others to the light cells
+    let balanced = (total + dark)
+        .checked_sub(light)
+        .filter(|sum| sum.is_multiple_of(2))
+        .and_then(|sum| subset_sums(&imbalances).get((sum / 2) as usize).copied())
+        .unwrap_or(false);
+    (!balanced).then_some(DeadEnd::CheckerboardParity { dark, light })
+}
+
+const fn board_mask(cells: usize) -> BitBoard {
+    if cells >= 64 { BitBoard::MAX } else { (1 << cells) - 1 }
+}
+
+const fn is_dark(row: usize, col: usize) -> bool {
+    (row + col).is_multiple_of(2)
+}
+
+/// Returns the difference between the dark and light cells covered by a piece, whatever its placement.
+fn imbalance(matrix: &DMatrix<u32>) -> u32 {
+    let mut difference = 0i32;
+    for row in 0..matrix.nrows() {
+        for col in 0..matrix.ncols() {
+            if matrix[(row, col)] != 0 {
+                difference += if is_dark(row, col) { 1 } else { -1 };
+            }
+        }
+    }
+    difference.unsigned_abs()
+}
+
+/// Returns, for each total from 0 to the sum of `values`, whether some of the values add up to it.
+fn subset_sums(values: &[u32]) -> Vec<bool> {
+    let mut sums = vec![false; values.iter().sum::<u32>() as usize + 1];
+    sums[0] = true;
+    for &value in values {
+        let value = value as usize;
+        for sum in (value..sums.len()).rev() {
+            sums[sum] |= sums[sum - value];
+        }
+    }
+    sums
+}
+
+/// Splits the cells of `free` into regions of cells connected by their sides, in reading order.
+fn regions(mut free: BitBoard, cols: usize) -> Vec<Vec<usize>> {
+    let mut regions = Vec::new();
+    while free != 0 {
+        let mut region = Vec::new();
+        let mut stack = vec![free.trailing_zeros() as usize];
+        free &= !(1 << stack[0]);
+        while let Some(cell) = stack.pop() {
+            region.push(cell);
+            let (row, col) = (cell / cols, cell % cols);
+            let neighbors = [
+                row.checked_sub(1).map(|row| row * cols + col),
+                Some((row + 1) * cols + col).filter(|&next| next < 64),
+                col.checked_sub(1).map(|col| row * cols + col),
+                Some(col + 1).filter(|&col| col < cols).map(|col| row * cols + col),
+            ];
+            for neighbor in neighbors.into_iter().flatten() {
+                if free & (1 << neighbor) != 0 {
+                    free &= !(1 << neighbor);
+                    stack.push(neighbor);
+                }
+            }
+        }
+        region.sort_unstable();
+        regions.push(region);
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::PieceName;
+
+    fn create_piece(rows: usize, cols: usize, values: &[u32]) -> Piece {
+        Piece { matrix: DMatrix::from_row_slice(rows, cols, values), color: 1, tui_color: 1 }
+    }
+
+    #[test]
+    fn test_piece_too_large() {
+        // The 3x1 bar does not fit a 2x2 board
+        let game = Game { columns: 2, pieces: vec![create_piece(3, 1, &[1, 1, 1]), create_piece(1, 1, &[1])] };
+        assert_eq!(explain(&game, 0, &game.piece_ids()), Some(DeadEnd::PieceTooLarge { piece: 0 }));
+        assert_eq!(DeadEnd::PieceTooLarge { piece: 0 }.to_string(), "piece 0 is too large for the board");
+    }
+
+    #[test]
+    fn test_no_room_and_isolated_region() {
+        // 2x3 board with a single cell, a 1x2 piece and an L
+        let game = Game {
+            columns: 3,
+            pieces: vec![create_piece(1, 1, &[1]), create_piece(1, 2, &[1, 1]), create_piece(2, 2, &[1, 0, 1, 1])],
+        };
+        // The 1x2 piece standing in the middle column leaves single columns on both sides
+        let middle = 0b010_010;
+        assert_eq!(explain(&game, middle, &[0, 2]), Some(DeadEnd::NoRoomForPiece { piece: 2 }));
+        assert_eq!(explain(&game, 0, &game.piece_ids()), None);
+        assert_eq!(explain(&game, 0b000_001, &[1, 2]), None);
+
+        // On a 2x4 board, a 1x2 piece standing in the second column cuts off the first one, too small for an L
+        let l = create_piece(2, 2, &[1, 0, 1, 1]);
+        let game = Game { columns: 4, pieces: vec![create_piece(2, 1, &[1, 1]), l.clone(), l] };
+        let dead_end = DeadEnd::IsolatedRegion { cells: vec![(0, 0), (1, 0)] };
+        assert_eq!(explain(&game, 0b0010_0010, &[1, 2]), Some(dead_end.clone()));
+        assert_eq!(dead_end.to_string(), "the pieces left cannot fill the free cells at (1, 1), (2, 1)");
+    }
+
+    #[test]
+    fn test_checkerboard_parity() {
+        // Five T tetrominoes each cover 3 cells of one color and 1 of the other, and a 4x5 board has 10 of each
+        let game = Game { columns: 5, pieces: vec![PieceName::BlueT4.piece(); 5] };
+        assert!(game.is_valid());
+        assert_eq!(explain(&game, 0, &game.piece_ids()), Some(DeadEnd::CheckerboardParity { dark: 10, light: 10 }));
+        assert_eq!(explain_unsolvable(&game, 0, &game.piece_ids()), DeadEnd::CheckerboardParity { dark: 10, light: 10 });
+    }
+
+    #[test]
+    fn test_no_arrangement() {
+        // On a 4x2 board, the zigzag always cuts off two corners from the square: the sizes and colors add up, but the shapes do not
+        let game = Game { columns: 2, pieces: vec![PieceName::VioletSquare4.piece(), PieceName::YellowZigZag4.piece()] };
+        assert!(game.is_valid());
+        assert_eq!(explain(&game, 0, &game.piece_ids()), Some(DeadEnd::NoArrangement));
+        assert_eq!(explain_unsolvable(&game, 0, &game.piece_ids()), DeadEnd::NoArrangement);
+    }
+
+    #[test]
+    fn test_regions() {
+        // A 3x3 board with the middle column occupied
+        let free = 0b101_101_101;
+        assert_eq!(regions(free, 3), vec![vec![0, 3, 6], vec![2, 5, 8]]);
+    }
+}
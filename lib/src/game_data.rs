@@ -1,6 +1,7 @@
 
 use nalgebra::DMatrix;
 
+use crate::matrix_tools::rotation_variants;
 use crate::models::{Game, Piece};
 
 // Color constants for game pieces (24-bit RGB hex values)
@@ -48,6 +49,83 @@ pub enum PieceName {
 }
 
 impl PieceName {
+    /// All piece names, in declaration order.
+    pub const ALL: [Self; 16] = [
+        Self::RedSquare1,
+        Self::TanBar2,
+        Self::BrownL3,
+        Self::OrangeBar3,
+        Self::PinkBar4,
+        Self::GreenL4,
+        Self::BlueT4,
+        Self::YellowZigZag4,
+        Self::VioletSquare4,
+        Self::OrangeL5,
+        Self::BrownT5,
+        Self::VioletZigZag5,
+        Self::BlueL5,
+        Self::PinkNotSquare5,
+        Self::YellowU5,
+        Self::BlueS5,
+    ];
+
+    /// Returns the letter printed on the piece when colors alone are not enough
+    /// to tell pieces apart: `A` for [`Self::RedSquare1`] to `P` for [`Self::BlueS5`].
+    #[must_use]
+    pub const fn letter(self) -> char {
+        (b'A' + self as u8) as char
+    }
+
+    /// Returns the piece with the given name, such as `BlueT4`.
+    ///
+    /// The comparison ignores case, `-` and `_`, so `blue-t4` and `BLUE_T4` are accepted too.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalize = |name: &str| -> String {
+            name.chars()
+                .filter(|c| !matches!(c, '-' | '_'))
+                .map(|c| c.to_ascii_lowercase())
+                .collect()
+        };
+        let name = normalize(name);
+        Self::ALL.into_iter().find(|piece| normalize(&format!("{piece:?}")) == name)
+    }
+
+    /// Returns a short human-readable description of the piece, such as `Blue T`.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::RedSquare1 => "Red square",
+            Self::TanBar2 => "Tan bar",
+            Self::BrownL3 => "Brown L",
+            Self::OrangeBar3 => "Orange bar",
+            Self::PinkBar4 => "Pink bar",
+            Self::GreenL4 => "Green L",
+            Self::BlueT4 => "Blue T",
+            Self::YellowZigZag4 => "Yellow zigzag",
+            Self::VioletSquare4 => "Violet square",
+            Self::OrangeL5 => "Orange L",
+            Self::BrownT5 => "Brown T",
+            Self::VioletZigZag5 => "Violet zigzag",
+            Self::BlueL5 => "Blue L",
+            Self::PinkNotSquare5 => "Pink not-square",
+            Self::YellowU5 => "Yellow U",
+            Self::BlueS5 => "Blue S",
+        }
+    }
+
+    /// Identifies a piece from its shape, in any rotation or reflection.
+    ///
+    /// Non-zero values of `matrix` are occupied cells, whatever their color.
+    /// Returns `None` if the shape is not one of the game pieces.
+    #[must_use]
+    pub fn from_matrix(matrix: &DMatrix<u32>) -> Option<Self> {
+        let shape = matrix.map(|cell| u32::from(cell != 0));
+        Self::ALL
+            .into_iter()
+            .find(|name| rotation_variants(&name.piece().matrix).contains(&shape))
+    }
+
     /// Creates a `Piece` instance from this piece name.
     #[must_use]
     pub fn piece(&self) -> Piece {
@@ -168,3 +246,35 @@ impl Game {
      }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter() {
+        assert_eq!(PieceName::RedSquare1.letter(), 'A');
+        assert_eq!(PieceName::BlueS5.letter(), 'P');
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(PieceName::from_name("BlueT4"), Some(PieceName::BlueT4));
+        assert_eq!(PieceName::from_name("blue-t4"), Some(PieceName::BlueT4));
+        assert_eq!(PieceName::from_name("PINK_NOT_SQUARE5"), Some(PieceName::PinkNotSquare5));
+        assert_eq!(PieceName::from_name("blue"), None);
+    }
+
+    #[test]
+    fn test_from_matrix() {
+        for name in PieceName::ALL {
+            assert_eq!(PieceName::from_matrix(&name.piece().matrix), Some(name));
+        }
+
+        // Rotated and colored L of 3 cells
+        let rotated = DMatrix::from_row_slice(2, 2, &[7, 7, 0, 7]);
+        assert_eq!(PieceName::from_matrix(&rotated), Some(PieceName::BrownL3));
+
+        let unknown = DMatrix::from_row_slice(1, 6, &[1; 6]);
+        assert_eq!(PieceName::from_matrix(&unknown), None);
+    }
+}
@@ -2,11 +2,50 @@ use nalgebra::DMatrix;
 
 use crate::models::{Game, Piece};
 use crate::matrix_tools;
-use crate::bitboard::{BitBoard, generate_positions};
+use crate::bitboard::{BitBoard, generate_positions, matrix_to_bitboard};
+
+/// Number of bits the piece id is shifted by when it is encoded into a cell value.
+const PIECE_ID_SHIFT: u32 = 24;
+
+/// Returns the index in [`Game::pieces`] of the piece covering a solution cell.
+///
+/// Solutions produced by [`GameResolver`] store `piece index + 1` in the high 8 bits
+/// of each cell, on top of the piece's 24-bit color.
+/// Returns `None` for empty cells and for cells without an encoded piece id.
+#[must_use]
+pub const fn piece_index(cell: u32) -> Option<usize> {
+    match cell >> PIECE_ID_SHIFT {
+        0 => None,
+        id => Some(id as usize - 1),
+    }
+}
+
+/// Encodes the index of a piece in [`Game::pieces`] into a cell color, as decoded by [`piece_index`].
+///
+/// # Panics
+///
+/// Panics if `piece_idx` does not fit in 8 bits.
+#[must_use]
+pub fn with_piece_index(color: u32, piece_idx: usize) -> u32 {
+    let piece_id = u32::try_from(piece_idx + 1).ok().filter(|&id| id <= 0xFF).expect("Too many pieces");
+    (color & 0x00FF_FFFF) | (piece_id << PIECE_ID_SHIFT)
+}
+
+/// Returns a copy of `piece` whose color also encodes its index in the game.
+fn piece_with_id(piece: &Piece, piece_idx: usize) -> Piece {
+    Piece {
+        matrix: piece.matrix.clone(),
+        color: with_piece_index(piece.color, piece_idx),
+        tui_color: piece.tui_color,
+    }
+}
+
+/// Every placement of one piece, as a bitboard for collision tests and a colored matrix.
+type Placements = Vec<(BitBoard, DMatrix<u32>)>;
 
 // Helper context and DFS for paginated resolution
 struct PageCtx<'a> {
-    precomputed: &'a [Vec<(BitBoard, DMatrix<u32>)>],
+    precomputed: &'a [Placements],
     start: usize,
     end: usize,
 }
@@ -50,8 +89,10 @@ pub trait GameResolverTrait {
     fn resolve(&self, game: &Game) -> Vec<DMatrix<u32>>;
 
     /// Finds the number of valid solutions for the given game without allocating solution matrices.
+    ///
+    /// Counts with a [`PlacementTable`], so invalid games have no solutions.
     fn resolve_count(&self, game: &Game) -> u32 {
-        u32::try_from(self.resolve(game).len()).expect("solution count exceeds u32::MAX")
+        u32::try_from(PlacementTable::new(game).count()).expect("solution count exceeds u32::MAX")
     }
     
     /// Generates all unique variants (rotations and reflections) of a piece.
@@ -88,16 +129,8 @@ impl GameResolverTrait for GameResolver {
         piece_indices.sort_by_key(|&i| std::cmp::Reverse(game.pieces[i].cells()));
 
         for &piece_idx in &piece_indices {
-            let piece = &game.pieces[piece_idx];
-            // Encode piece index into the high 8 bits of its color (as before).
-            let piece_id = u32::try_from(piece_idx).expect("Too many pieces") + 1;
-            let color_with_id = piece.color | (piece_id << 24);
-
-            let piece_with_id = Piece {
-                matrix: piece.matrix.clone(),
-                color: color_with_id,
-                tui_color: piece.tui_color,
-            };
+            // Encode piece index into the high 8 bits of its color.
+            let piece_with_id = piece_with_id(&game.pieces[piece_idx], piece_idx);
 
             let mut next_solutions: Vec<(BitBoard, DMatrix<u32>)> = Vec::new();
 
@@ -124,7 +157,7 @@ impl GameResolverTrait for GameResolver {
             }
 
             #[cfg(debug_assertions)]
-            println!("- Found {} possible boards for piece {}", solutions.len(), piece_idx);
+            eprintln!("- Found {} possible boards for piece {}", solutions.len(), piece_idx);
         }
 
         // Return the colored matrices from the solutions
@@ -156,27 +189,49 @@ impl GameResolverTrait for GameResolver {
             return Vec::new();
         }
 
-        // Order pieces by decreasing cell count for stronger pruning
-        let mut ordered_indices: Vec<_> = (0..game.pieces.len()).collect();
-        ordered_indices.sort_by_key(|&i| std::cmp::Reverse(game.pieces[i].cells()));
+        PlacementTable::new(game).page(page_index, page_size)
+    }
+}
 
-        // Precompute variants and placements for each ordered piece
-        let mut precomputed: Vec<Vec<(BitBoard, DMatrix<u32>)>> = Vec::with_capacity(ordered_indices.len());
-        for (order_pos, &piece_idx) in ordered_indices.iter().enumerate() {
-            let piece = &game.pieces[piece_idx];
-            let piece_id = u32::try_from(order_pos).expect("Too many pieces") + 1;
-            let color_with_id = piece.color | (piece_id << 24);
-            let piece_with_id = Piece {
-                matrix: piece.matrix.clone(),
-                color: color_with_id,
-                tui_color: piece.tui_color,
-            };
-            let mut list: Vec<(BitBoard, DMatrix<u32>)> = Vec::new();
-            for variant in self.piece_variants(&piece_with_id) {
-                list.extend(generate_positions(&variant, rows, cols));
-            }
-            list.sort_by_key(|(bits, _)| *bits);
-            precomputed.push(list);
+/// Every placement of each piece of a game, computed once to run several searches on the same game.
+///
+/// Building the table is the setup cost of [`GameResolverTrait::resolve_page`];
+/// callers answering many queries about the same game can keep the table instead.
+/// Searches give the same solutions, in the same order, as [`GameResolverTrait::resolve_page`].
+pub struct PlacementTable {
+    rows: usize,
+    cols: usize,
+    /// Placements of each piece, largest pieces first; `None` for an invalid game.
+    placements: Option<Vec<Placements>>,
+}
+
+impl PlacementTable {
+    /// Computes the placements of all the pieces of `game`.
+    ///
+    /// Games rejected by [`Game::validate`], including boards larger than 64 cells, have no solutions.
+    #[must_use]
+    pub fn new(game: &Game) -> Self {
+        let rows = game.cells().checked_div(game.columns).unwrap_or(0) as usize;
+        let cols = game.columns as usize;
+        let placements = game
+            .validate()
+            .is_ok()
+            .then(|| GameResolver.precompute_placements(game, (0..game.pieces.len()).collect(), rows, cols));
+        Self { rows, cols, placements }
+    }
+
+    /// Returns the number of rows and columns of the board.
+    #[must_use]
+    pub const fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns a page of solutions, as [`GameResolverTrait::resolve_page`].
+    #[must_use]
+    pub fn page(&self, page_index: usize, page_size: usize) -> Vec<DMatrix<u32>> {
+        let Some(precomputed) = &self.placements else { return Vec::new() };
+        if page_size == 0 {
+            return Vec::new();
         }
 
         let mut results: Vec<DMatrix<u32>> = Vec::new();
@@ -185,15 +240,222 @@ impl GameResolverTrait for GameResolver {
         let mut count = 0usize;
 
         let empty_board_bits: BitBoard = 0;
-        let empty_board_matrix = DMatrix::<u32>::zeros(rows, cols);
-        let mut ctx = PageCtx { precomputed: &precomputed, start, end };
+        let empty_board_matrix = DMatrix::<u32>::zeros(self.rows, self.cols);
+        let mut ctx = PageCtx { precomputed, start, end };
         dfs_page(&mut ctx, 0, empty_board_bits, &empty_board_matrix, &mut results, &mut count);
         results
     }
+
+    /// Returns the number of solutions, without building their matrices.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.placements.as_ref().map_or(0, |precomputed| dfs_count(precomputed, 0))
+    }
+}
+
+/// A search in progress, at one piece of the game.
+struct Frame {
+    /// Index of the next placement to try for the piece.
+    next: usize,
+    /// Cells covered by the pieces placed before this one.
+    bits: BitBoard,
+}
+
+/// A search for the solutions of a game that runs in small steps, to be resumed later.
+///
+/// Each call to [`SolverSession::step`] examines a bounded number of placements, so that
+/// callers without threads, such as a browser tab, can interleave the search with other work
+/// and stop it at any time. Solutions come in the same order as [`GameResolverTrait::resolve_page`].
+pub struct SolverSession {
+    table: PlacementTable,
+    /// One frame per piece being placed; empty once the search is over.
+    stack: Vec<Frame>,
+    /// Index of the placement chosen for each piece of the stack.
+    chosen: Vec<usize>,
+    solutions_found: usize,
+    placements_tried: u64,
+}
+
+impl SolverSession {
+    /// Starts a search for the solutions of `game`.
+    ///
+    /// Games rejected by [`Game::validate`], including boards larger than 64 cells, have no solutions.
+    #[must_use]
+    pub fn new(game: &Game) -> Self {
+        let table = PlacementTable::new(game);
+        let pieces = table.placements.as_ref().map_or(0, Vec::len);
+        let stack = if pieces == 0 { Vec::new() } else { vec![Frame { next: 0, bits: 0 }] };
+        Self { table, stack, chosen: vec![0; pieces], solutions_found: 0, placements_tried: 0 }
+    }
+
+    /// Tries up to `max_placements` placements, returning the solutions found meanwhile.
+    pub fn step(&mut self, max_placements: usize) -> Vec<DMatrix<u32>> {
+        let mut solutions = Vec::new();
+        let Some(precomputed) = &self.table.placements else { return solutions };
+        for _ in 0..max_placements {
+            let depth = self.stack.len();
+            let Some(frame) = self.stack.last_mut() else { break };
+            let placements = &precomputed[depth - 1];
+            let Some((placement_bits, _)) = placements.get(frame.next) else {
+                self.stack.pop();
+                continue;
+            };
+            self.chosen[depth - 1] = frame.next;
+            frame.next += 1;
+            self.placements_tried += 1;
+            if frame.bits & placement_bits != 0 {
+                continue;
+            }
+
+            let bits = frame.bits | placement_bits;
+            if depth == precomputed.len() {
+                let mut solution = DMatrix::zeros(self.table.rows, self.table.cols);
+                for (placements, &index) in precomputed.iter().zip(&self.chosen) {
+                    solution += &placements[index].1;
+                }
+                solutions.push(solution);
+                self.solutions_found += 1;
+            } else {
+                self.stack.push(Frame { next: 0, bits });
+            }
+        }
+        solutions
+    }
+
+    /// Returns whether all the solutions were found.
+    #[must_use]
+    pub const fn is_done(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Returns the number of solutions found so far.
+    #[must_use]
+    pub const fn solutions_found(&self) -> usize {
+        self.solutions_found
+    }
+
+    /// Returns the number of placements tried so far.
+    #[must_use]
+    pub const fn placements_tried(&self) -> u64 {
+        self.placements_tried
+    }
+
+    /// Estimates the part of the search already done, from 0 to 1.
+    ///
+    /// Each piece splits the remaining search evenly between its placements, so the estimate
+    /// is rough when some placements lead to much larger searches than others.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // placement counts are far below 2^52
+    pub fn progress(&self) -> f64 {
+        let Some(precomputed) = &self.table.placements else { return 1.0 };
+        if self.is_done() {
+            return 1.0;
+        }
+        let mut progress = 0.0;
+        let mut share = 1.0;
+        let top = self.stack.len() - 1;
+        for (depth, (frame, placements)) in self.stack.iter().zip(precomputed).enumerate() {
+            // Below the top, the placement at `next - 1` is being explored by the next frame
+            let finished = if depth == top { frame.next } else { frame.next - 1 };
+            let total = placements.len().max(1) as f64;
+            progress += share * finished as f64 / total;
+            share /= total;
+        }
+        progress
+    }
+}
+
+fn dfs_count(precomputed: &[Placements], boards_bits: BitBoard) -> usize {
+    let Some((placements, rest)) = precomputed.split_first() else { return 1 };
+    placements
+        .iter()
+        .filter(|(placement_bits, _)| boards_bits & placement_bits == 0)
+        .map(|(placement_bits, _)| dfs_count(rest, boards_bits | placement_bits))
+        .sum()
 }
 
 
 impl GameResolver {
+    /// Finds up to `limit` completions of a partially filled board.
+    ///
+    /// `board` holds the pieces already placed, encoded like the solutions of the solver
+    /// (see [`with_piece_index`]), and `remaining` the indices in [`Game::pieces`] of the pieces
+    /// left to place. The returned solutions contain both the placed and the remaining pieces,
+    /// in the same order as [`GameResolverTrait::resolve_page`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board exceeds 64 cells.
+    #[must_use]
+    pub fn resolve_from(&self, game: &Game, board: &DMatrix<u32>, remaining: &[usize], limit: usize) -> Vec<DMatrix<u32>> {
+        let (rows, cols) = board.shape();
+        assert!(rows * cols <= 64, "Board size exceeds 64 cells (rows * cols = {}), which is the limit for the bitboard implementation.", rows * cols);
+        let occupied = matrix_to_bitboard(board, rows, cols, 0, 0);
+        self.complete(game, occupied, remaining, (rows, cols), limit)
+            .into_iter()
+            .map(|completion| completion + board)
+            .collect()
+    }
+
+    /// Finds up to `limit` ways to place the `remaining` pieces on the free cells of `occupied`.
+    ///
+    /// `occupied` marks the cells already covered on the board of `game`, and `remaining` holds
+    /// the indices in [`Game::pieces`] of the pieces left to place. The returned boards contain
+    /// only the remaining pieces, encoded like the solutions of the solver, in the same order as
+    /// [`GameResolver::resolve_from`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board exceeds 64 cells.
+    #[must_use]
+    pub fn resolve_from_bitboard(&self, game: &Game, occupied: BitBoard, remaining: &[usize], limit: usize) -> Vec<DMatrix<u32>> {
+        let rows = usize::try_from(game.rows()).expect("Row count too large");
+        let cols = usize::try_from(game.columns).expect("Column count too large");
+        assert!(rows * cols <= 64, "Board size exceeds 64 cells (rows * cols = {}), which is the limit for the bitboard implementation.", rows * cols);
+        self.complete(game, occupied, remaining, (rows, cols), limit)
+    }
+
+    /// Places the `remaining` pieces around `occupied` on a board of `shape`, as [`GameResolver::resolve_from_bitboard`].
+    fn complete(&self, game: &Game, occupied: BitBoard, remaining: &[usize], shape: (usize, usize), limit: usize) -> Vec<DMatrix<u32>> {
+        let (rows, cols) = shape;
+        if limit == 0 {
+            return Vec::new();
+        }
+
+        let precomputed = self.precompute_placements(game, remaining.to_vec(), rows, cols);
+
+        let mut results = Vec::new();
+        let mut count = 0usize;
+        let mut ctx = PageCtx { precomputed: &precomputed, start: 0, end: limit };
+        dfs_page(&mut ctx, 0, occupied, &DMatrix::zeros(rows, cols), &mut results, &mut count);
+        results
+    }
+
+    /// Computes every placement of each piece of `piece_indices`, largest pieces first.
+    fn precompute_placements(
+        &self,
+        game: &Game,
+        mut piece_indices: Vec<usize>,
+        rows: usize,
+        cols: usize,
+    ) -> Vec<Placements> {
+        // Order pieces by decreasing cell count for stronger pruning
+        piece_indices.sort_by_key(|&i| std::cmp::Reverse(game.pieces[i].cells()));
+
+        // Precompute variants and placements for each ordered piece
+        piece_indices
+            .into_iter()
+            .map(|piece_idx| {
+                let piece_with_id = piece_with_id(&game.pieces[piece_idx], piece_idx);
+                let mut list: Vec<(BitBoard, DMatrix<u32>)> = Vec::new();
+                for variant in self.piece_variants(&piece_with_id) {
+                    list.extend(generate_positions(&variant, rows, cols));
+                }
+                list.sort_by_key(|(bits, _)| *bits);
+                list
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -252,6 +514,150 @@ mod tests {
         assert!(solutions.is_empty());
     }
 
+    #[test]
+    fn test_piece_index() {
+        assert_eq!(piece_index(0), None);
+        assert_eq!(piece_index(0x00FF_0000), None);
+        assert_eq!(piece_index(0x01FF_0000), Some(0));
+        assert_eq!(piece_index(0x12FF_0000), Some(17));
+        assert_eq!(piece_index(with_piece_index(0x00FF_0000, 17)), Some(17));
+    }
+
+    #[test]
+    fn test_resolve_page_encodes_game_piece_index() {
+        // A 1-cell piece listed first is placed last by the solver,
+        // but must still be reported as piece 0.
+        let p1 = create_piece(1, 1, &[1]);
+        let p2 = create_piece(2, 2, &[1, 0, 1, 1]);
+        let game = Game {
+            columns: 2,
+            pieces: vec![p1, p2],
+        };
+
+        let resolver = GameResolver;
+        let solutions = resolver.resolve_page(&game, 0, 10);
+        assert_eq!(solutions.len(), 4);
+        for solution in solutions {
+            let singles = solution.iter().filter(|&&cell| piece_index(cell) == Some(0)).count();
+            assert_eq!(singles, 1);
+        }
+    }
+
+    #[test]
+    fn test_resolve_pages_are_consecutive() {
+        // Pages requested one at a time must add up to the full list, without gaps or repeats
+        let game = Game {
+            columns: 3,
+            pieces: vec![
+                create_piece(1, 1, &[1]),
+                create_piece(1, 2, &[1, 1]),
+                create_piece(2, 2, &[1, 0, 1, 1]),
+            ],
+        };
+
+        let resolver = GameResolver;
+        let all = resolver.resolve_page(&game, 0, usize::MAX);
+        let paged: Vec<_> = (0..all.len()).flat_map(|page| resolver.resolve_page(&game, page, 1)).collect();
+        assert!(all.len() > 1);
+        assert_eq!(paged, all);
+    }
+
+    #[test]
+    fn test_resolve_from() {
+        // 2x3 board, the 1x1 piece already placed at the top left
+        let game = Game {
+            columns: 3,
+            pieces: vec![
+                create_piece(1, 1, &[1]),
+                create_piece(1, 2, &[1, 1]),
+                create_piece(2, 2, &[1, 0, 1, 1]),
+            ],
+        };
+        let mut board = DMatrix::zeros(2, 3);
+        board[(0, 0)] = with_piece_index(1, 0);
+
+        let resolver = GameResolver;
+        let completions = resolver.resolve_from(&game, &board, &[1, 2], usize::MAX);
+        let expected: Vec<_> = resolver
+            .resolve(&game)
+            .into_iter()
+            .filter(|solution| solution[(0, 0)] == board[(0, 0)])
+            .collect();
+        assert!(!completions.is_empty());
+        assert_eq!(completions.len(), expected.len());
+        assert!(completions.iter().all(|solution| expected.contains(solution)));
+
+        let occupied = matrix_to_bitboard(&board, 2, 3, 0, 0);
+        let remaining_only: Vec<_> = completions.iter().map(|solution| solution - &board).collect();
+        assert_eq!(resolver.resolve_from_bitboard(&game, occupied, &[1, 2], usize::MAX), remaining_only);
+
+        // A 1x2 piece in the middle of the top row leaves no room for the L
+        let mut blocked = DMatrix::zeros(2, 3);
+        blocked[(0, 1)] = with_piece_index(1, 1);
+        blocked[(1, 1)] = with_piece_index(1, 1);
+        assert!(resolver.resolve_from(&game, &blocked, &[0, 2], 1).is_empty());
+    }
+
+    #[test]
+    fn test_placement_table() {
+        let game = Game {
+            columns: 3,
+            pieces: vec![
+                create_piece(1, 1, &[1]),
+                create_piece(1, 2, &[1, 1]),
+                create_piece(2, 2, &[1, 0, 1, 1]),
+            ],
+        };
+
+        let resolver = GameResolver;
+        let table = PlacementTable::new(&game);
+        assert_eq!(table.shape(), (2, 3));
+        assert_eq!(table.count(), resolver.resolve(&game).len());
+        assert_eq!(resolver.resolve_count(&game) as usize, table.count());
+        assert_eq!(table.page(1, 3), resolver.resolve_page(&game, 1, 3));
+
+        let invalid = PlacementTable::new(&Game { columns: 4, ..game });
+        assert_eq!(invalid.count(), 0);
+        assert!(invalid.page(0, 10).is_empty());
+
+        // 18 pieces on 5 columns: 70 cells, more than the bitboard holds
+        let too_large = PlacementTable::new(&Game::game_with_all_pieces());
+        assert_eq!(too_large.count(), 0);
+        assert!(too_large.page(0, 10).is_empty());
+        assert!(SolverSession::new(&Game::game_with_all_pieces()).is_done());
+    }
+
+    #[test]
+    fn test_solver_session() {
+        let game = Game {
+            columns: 3,
+            pieces: vec![
+                create_piece(1, 1, &[1]),
+                create_piece(1, 2, &[1, 1]),
+                create_piece(2, 2, &[1, 0, 1, 1]),
+            ],
+        };
+
+        let mut session = SolverSession::new(&game);
+        assert!(session.progress().abs() < f64::EPSILON);
+        let mut solutions = Vec::new();
+        let mut last_progress = 0.0;
+        while !session.is_done() {
+            solutions.extend(session.step(3));
+            assert!(session.progress() >= last_progress);
+            last_progress = session.progress();
+        }
+        assert!((session.progress() - 1.0).abs() < f64::EPSILON);
+        assert_eq!(solutions, GameResolver.resolve_page(&game, 0, usize::MAX));
+        assert_eq!(session.solutions_found(), solutions.len());
+        assert!(session.placements_tried() > 0);
+        assert!(session.step(10).is_empty());
+
+        let mut invalid = SolverSession::new(&Game { columns: 4, ..game });
+        assert!(invalid.is_done());
+        assert!(invalid.step(10).is_empty());
+    }
+
     #[test]
     #[should_panic(expected = "Board size exceeds 64 cells")]
     fn test_resolve_too_large_board() {
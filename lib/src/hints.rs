@@ -0,0 +1,250 @@
+//! Hints for a partially filled board: whether it can still be completed, which piece to
+//! place next and where, and which placed piece stands in the way of every solution.
+
+use nalgebra::DMatrix;
+
+use crate::bitboard::BitBoard;
+use crate::dead_end::{self, DeadEnd};
+use crate::game_resolver::{piece_index, GameResolver};
+use crate::models::{Game, Placement, SolutionError};
+
+/// How much a hint gives away, from the least to the most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HintLevel {
+    /// Only whether the board can still be completed.
+    Solvable,
+    /// Which piece to place next.
+    Piece,
+    /// Which piece to place next, in which orientation and where.
+    Placement,
+}
+
+/// Answer of a [`HintEngine`] for a partial board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hint {
+    /// Every piece is already on the board, which is a solution.
+    Complete,
+    /// The board can still be completed.
+    Solvable,
+    /// The board can still be completed by placing this piece next, given as its index in [`Game::pieces`].
+    Piece(usize),
+    /// The board can still be completed by placing a piece there next.
+    Placement(Placement),
+    /// The board cannot be completed, for `reason`. `blocking` holds the placed pieces that,
+    /// taken off on their own, make it solvable again, and is empty when more pieces must go.
+    Unsolvable { reason: DeadEnd, blocking: Vec<usize> },
+}
+
+/// Answers questions about a partial board of a game, by searching for ways to complete it.
+///
+/// The board is encoded like the solutions of the solver (see
+/// [`with_piece_index`](crate::game_resolver::with_piece_index)), with 0 for empty cells.
+pub struct HintEngine<'a> {
+    game: &'a Game,
+    board: DMatrix<u32>,
+    /// Cells covered by each piece of the game, empty for the pieces not on the board.
+    cells: Vec<BitBoard>,
+}
+
+impl<'a> HintEngine<'a> {
+    /// Reads the pieces placed on `board`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SolutionError::InvalidGame`] when the game cannot be solved, [`SolutionError::WrongSize`]
+    /// when the board does not have the size of the game board, and [`SolutionError::UnknownPiece`]
+    /// when a cell holds a piece that is not part of the game.
+    pub fn new(game: &'a Game, board: &DMatrix<u32>) -> Result<Self, SolutionError> {
+        game.validate().map_err(SolutionError::InvalidGame)?;
+        let (rows, columns) = board.shape();
+        if u32::try_from(rows).ok() != Some(game.rows()) || u32::try_from(columns).ok() != Some(game.columns) {
+            return Err(SolutionError::WrongSize { rows, columns, expected_rows: game.rows(), expected_columns: game.columns });
+        }
+
+        let mut cells = vec![0; game.pieces.len()];
+        for row in 0..rows {
+            for col in 0..columns {
+                let Some(piece) = piece_index(board[(row, col)]) else { continue };
+                *cells.get_mut(piece).ok_or(SolutionError::UnknownPiece { row, col, piece })? |= 1 << (row * columns + col);
+            }
+        }
+        Ok(Self::from_cells(game, board.clone(), cells))
+    }
+
+    /// Builds an engine from the cells covered by each piece, without checking them.
+    ///
+    /// `game` must be valid, `board` must have the size of its board, and `cells` must hold
+    /// the cells of each piece of `board`, as [`HintEngine::new`] finds them.
+    pub(crate) const fn from_cells(game: &'a Game, board: DMatrix<u32>, cells: Vec<BitBoard>) -> Self {
+        Self { game, board, cells }
+    }
+
+    /// Returns the indices of the pieces not on the board.
+    #[must_use]
+    pub fn remaining(&self) -> Vec<usize> {
+        self.cells.iter().enumerate().filter(|(_, &bits)| bits == 0).map(|(piece, _)| piece).collect()
+    }
+
+    fn occupied(&self) -> BitBoard {
+        self.cells.iter().fold(0, |occupied, bits| occupied | bits)
+    }
+
+    /// Returns the first solution of the game that keeps the pieces of the board where they are.
+    #[must_use]
+    pub fn completion(&self) -> Option<DMatrix<u32>> {
+        let remaining = self.remaining();
+        let completion = GameResolver.resolve_from_bitboard(self.game, self.occupied(), &remaining, 1).pop()?;
+        // The completion only holds the remaining pieces
+        Some(completion + &self.board)
+    }
+
+    /// Whether the pieces left can still be placed around the ones on the board.
+    #[must_use]
+    pub fn is_solvable(&self) -> bool {
+        let remaining = self.remaining();
+        !GameResolver.resolve_from_bitboard(self.game, self.occupied(), &remaining, 1).is_empty()
+    }
+
+    /// Returns where to place a piece next to keep the board solvable, or `None` when it cannot be completed.
+    ///
+    /// The piece is the one covering the first free cell in reading order in the first completion of the board,
+    /// so that following the hints fills the board from the top left.
+    #[must_use]
+    pub fn next_placement(&self) -> Option<Placement> {
+        let occupied = self.occupied();
+        let solution = self.completion()?;
+        let columns = self.game.columns as usize;
+        let cell = (0..solution.len()).find(|&cell| occupied & (1 << cell) == 0)?;
+        let piece = piece_index(solution[(cell / columns, cell % columns)])?;
+        self.game.placements(&solution).ok()?.get(piece).copied()
+    }
+
+    /// Returns why the board cannot be completed, or `None` when it can.
+    #[must_use]
+    pub fn dead_end(&self) -> Option<DeadEnd> {
+        dead_end::explain(self.game, self.occupied(), &self.remaining())
+    }
+
+    /// Returns the placed pieces that, taken off the board on their own, make it solvable again.
+    ///
+    /// The list is empty when the board is solvable, or when no single piece is to blame.
+    #[must_use]
+    pub fn blocking_pieces(&self) -> Vec<usize> {
+        if self.is_solvable() {
+            return Vec::new();
+        }
+        let occupied = self.occupied();
+        let mut remaining = self.remaining();
+        (0..self.cells.len())
+            .filter(|&piece| self.cells[piece] != 0)
+            .filter(|&piece| {
+                remaining.push(piece);
+                let solvable = !GameResolver.resolve_from_bitboard(self.game, occupied & !self.cells[piece], &remaining, 1).is_empty();
+                remaining.pop();
+                solvable
+            })
+            .collect()
+    }
+
+    /// Returns a hint giving away as much as `level`.
+    #[must_use]
+    pub fn hint(&self, level: HintLevel) -> Hint {
+        if self.cells.iter().all(|&bits| bits != 0) {
+            return Hint::Complete;
+        }
+        let Some(placement) = self.next_placement() else {
+            let reason = dead_end::explain_unsolvable(self.game, self.occupied(), &self.remaining());
+            return Hint::Unsolvable { reason, blocking: self.blocking_pieces() };
+        };
+        match level {
+            HintLevel::Solvable => Hint::Solvable,
+            HintLevel::Piece => Hint::Piece(placement.piece),
+            HintLevel::Placement => Hint::Placement(placement),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_resolver::with_piece_index;
+    use crate::models::Piece;
+
+    fn create_piece(rows: usize, cols: usize, values: &[u32]) -> Piece {
+        Piece { matrix: DMatrix::from_row_slice(rows, cols, values), color: 1, tui_color: 1 }
+    }
+
+    /// A 2x3 board with a single cell, a 1x2 piece and an L.
+    fn game() -> Game {
+        Game {
+            columns: 3,
+            pieces: vec![
+                create_piece(1, 1, &[1]),
+                create_piece(1, 2, &[1, 1]),
+                create_piece(2, 2, &[1, 0, 1, 1]),
+            ],
+        }
+    }
+
+    fn board(cells: &[(usize, usize, usize)]) -> DMatrix<u32> {
+        let mut board = DMatrix::zeros(2, 3);
+        for &(row, col, piece) in cells {
+            board[(row, col)] = with_piece_index(1, piece);
+        }
+        board
+    }
+
+    #[test]
+    fn test_hint_levels() {
+        let game = game();
+        let empty = board(&[]);
+        let engine = HintEngine::new(&game, &empty).unwrap();
+        assert!(engine.is_solvable());
+        assert_eq!(engine.dead_end(), None);
+        assert_eq!(engine.remaining(), vec![0, 1, 2]);
+        assert_eq!(engine.hint(HintLevel::Solvable), Hint::Solvable);
+
+        let Hint::Placement(placement) = engine.hint(HintLevel::Placement) else { panic!("expected a placement") };
+        assert_eq!((placement.row, placement.col), (0, 0));
+        assert_eq!(engine.hint(HintLevel::Piece), Hint::Piece(placement.piece));
+
+        // Following the hints completes the board
+        let mut board = empty;
+        for _ in 0..game.pieces.len() {
+            let engine = HintEngine::new(&game, &board).unwrap();
+            let Hint::Placement(placement) = engine.hint(HintLevel::Placement) else { panic!("expected a placement") };
+            let solution = engine.completion().unwrap();
+            let id = with_piece_index(1, placement.piece);
+            board += solution.map(|cell| if cell == id { id } else { 0 });
+        }
+        assert!(game.verify(&board).is_ok());
+        assert_eq!(HintEngine::new(&game, &board).unwrap().hint(HintLevel::Placement), Hint::Complete);
+    }
+
+    #[test]
+    fn test_blocking_pieces() {
+        let game = game();
+        // A 1x2 piece standing in the middle column leaves no room for the L
+        let blocked = board(&[(0, 1, 1), (1, 1, 1)]);
+        let engine = HintEngine::new(&game, &blocked).unwrap();
+        assert!(!engine.is_solvable());
+        assert_eq!(engine.next_placement(), None);
+        let reason = DeadEnd::NoRoomForPiece { piece: 2 };
+        assert_eq!(engine.dead_end(), Some(reason.clone()));
+        assert_eq!(engine.hint(HintLevel::Piece), Hint::Unsolvable { reason, blocking: vec![1] });
+
+        // Nothing blocks a solvable board
+        let solvable = board(&[(0, 1, 0)]);
+        assert!(HintEngine::new(&game, &solvable).unwrap().blocking_pieces().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_boards() {
+        let game = game();
+        assert!(matches!(HintEngine::new(&game, &DMatrix::zeros(3, 2)), Err(SolutionError::WrongSize { .. })));
+        assert_eq!(
+            HintEngine::new(&game, &board(&[(1, 2, 5)])).err(),
+            Some(SolutionError::UnknownPiece { row: 1, col: 2, piece: 5 })
+        );
+    }
+}
@@ -9,16 +9,33 @@
 //! - [`game_data`] - Predefined game pieces and configurations
 //! - [`game_resolver`] - Solver algorithm
 //! - [`svg_renderer`] - SVG visualization of solutions
-//! - [`matrix_tools`] - Matrix manipulation utilities
+//! - [`assembly`] - Step-by-step assembly order of a solution
+//! - [`play_session`] - A game being played, with undo and redo
+//! - [`hints`] - Graded hints for a partially filled board
+//! - [`dead_end`] - Explanations of why a board cannot be completed
+//! - [`palette`] - Named color palettes and pattern fills for the pieces
+//! - `png_renderer` - PNG rasterization of the SVG output (requires the `png` feature)
+//! - [`matrix_tools`] - Matrix manipulation utilities (rotations and reflections of pieces)
+//! - [`bitboard`] - 64-bit board representation used for collision detection
 
 pub mod models;
 pub mod game_data;
 pub mod game_resolver;
 pub mod svg_renderer;
-mod matrix_tools;
-mod bitboard;
+pub mod assembly;
+pub mod play_session;
+pub mod hints;
+pub mod dead_end;
+pub mod palette;
+#[cfg(feature = "png")]
+pub mod png_renderer;
+pub mod matrix_tools;
+pub mod bitboard;
 
 // Re-export commonly used types for convenience
-pub use models::{Piece, Game};
+pub use models::{Piece, PieceError, Game, GameError, Placement, SolutionError};
 pub use game_data::PieceName;
-pub use game_resolver::{GameResolver, GameResolverTrait};
\ No newline at end of file
+pub use game_resolver::{GameResolver, GameResolverTrait, PlacementTable, SolverSession};
+pub use play_session::{PlayError, PlayEvent, PlaySession};
+pub use hints::{Hint, HintEngine, HintLevel};
+pub use dead_end::DeadEnd;
\ No newline at end of file
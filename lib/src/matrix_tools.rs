@@ -8,8 +8,10 @@ use std::cmp;
 /// - 4 rotations (0°, 90°, 180°, 270°)
 /// - 4 reflections (mirrored versions of each rotation)
 ///
-/// Duplicate variants are automatically removed.
+/// Duplicate variants are automatically removed, keeping the first occurrence, so the order
+/// of the variants (and of the solutions found with them) is the same on every call.
 #[must_use]
+#[allow(clippy::missing_panics_doc)] // `variants` is never empty when unwrapped
 pub fn rotation_variants(matrix: &DMatrix<u32>) -> Vec<DMatrix<u32>> {
     let mut variants = vec![matrix.clone()];
     variants.push(rotate_matrix(variants.last().unwrap()));
@@ -22,8 +24,9 @@ pub fn rotation_variants(matrix: &DMatrix<u32>) -> Vec<DMatrix<u32>> {
     variants.push(rotate_matrix(variants.last().unwrap()));
 
     // Dedup
-    let hash_set: HashSet<DMatrix<u32>> = variants.into_iter().collect();
-    hash_set.into_iter().collect()
+    let mut seen = HashSet::new();
+    variants.retain(|variant| seen.insert(variant.clone()));
+    variants
 }
 
 /// Rotates a matrix 90 degrees clockwise.
@@ -40,6 +43,13 @@ pub fn rotate_matrix(matrix: &DMatrix<u32>) -> DMatrix<u32> {
     })
 }
 
+/// Mirrors a matrix horizontally (left becomes right).
+#[must_use]
+pub fn flip_matrix(matrix: &DMatrix<u32>) -> DMatrix<u32> {
+    let ncols = matrix.ncols();
+    DMatrix::from_fn(matrix.nrows(), ncols, |i, j| matrix[(i, ncols - 1 - j)])
+}
+
 /// Clamps all values in a matrix to a maximum value.
 ///
 /// Returns a new matrix where all values greater than `max_val` are replaced with `max_val`.
@@ -66,6 +76,16 @@ mod tests {
         assert_eq!(rotated, DMatrix::from_row_slice(2, 2, &[3, 1, 4, 2]));
     }
 
+    #[test]
+    fn test_flip_matrix() {
+        // L-shape
+        // 1 0      0 1
+        // 1 1  ->  1 1
+        let matrix = DMatrix::from_row_slice(2, 2, &[1, 0, 1, 1]);
+        assert_eq!(flip_matrix(&matrix), DMatrix::from_row_slice(2, 2, &[0, 1, 1, 1]));
+        assert!(rotation_variants(&matrix).contains(&flip_matrix(&matrix)));
+    }
+
     #[test]
     fn test_rotation_variants() {
         // L-shape
@@ -89,6 +109,9 @@ mod tests {
         let rect = DMatrix::from_row_slice(1, 2, &[1, 1]);
         let rect_variants = rotation_variants(&rect);
         assert_eq!(rect_variants.len(), 2);
+        // The original orientation comes first, the order is stable
+        assert_eq!(rect_variants[0], rect);
+        assert_eq!(rotation_variants(&matrix), rotation_variants(&matrix));
     }
 
     #[test]
@@ -1,6 +1,14 @@
 
+use std::fmt;
+
 use nalgebra::DMatrix;
 
+use crate::game_resolver::piece_index;
+use crate::matrix_tools::rotation_variants;
+
+/// Maximum number of cells of a board, imposed by the 64-bit bitboards used by the solver.
+pub const MAX_BOARD_CELLS: u32 = 64;
+
 /// Represents a game piece with a matrix pattern and color.
 ///
 /// Each piece has a matrix where non-zero values indicate occupied cells,
@@ -13,6 +21,53 @@ pub struct Piece {
 }
 
 impl Piece {
+    /// Creates a custom piece from its shape, in which non-zero values are the cells of the piece.
+    ///
+    /// Empty rows and columns around the shape are removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PieceError`] when the shape has no cells, or its cells are not connected
+    /// by their sides, or when `color` does not fit in 24 bits.
+    pub fn from_shape(shape: &DMatrix<u32>, color: u32) -> Result<Self, PieceError> {
+        if color > 0x00FF_FFFF {
+            return Err(PieceError::InvalidColor { color });
+        }
+        let cells: Vec<(usize, usize)> = (0..shape.nrows())
+            .flat_map(|row| (0..shape.ncols()).map(move |col| (row, col)))
+            .filter(|&cell| shape[cell] != 0)
+            .collect();
+        let (Some(top), Some(left)) = (cells.iter().map(|&(row, _)| row).min(), cells.iter().map(|&(_, col)| col).min()) else {
+            return Err(PieceError::Empty);
+        };
+        let bottom = cells.iter().map(|&(row, _)| row).max().unwrap_or(top);
+        let right = cells.iter().map(|&(_, col)| col).max().unwrap_or(left);
+        let matrix = DMatrix::from_fn(bottom - top + 1, right - left + 1, |row, col| u32::from(shape[(row + top, col + left)] != 0));
+
+        // Flood fill from the first cell, along the sides of the cells
+        let mut reached = vec![cells[0]];
+        let mut next = 0;
+        while let Some(&(row, col)) = reached.get(next) {
+            next += 1;
+            let neighbors = [
+                row.checked_sub(1).map(|row| (row, col)),
+                Some((row + 1, col)),
+                col.checked_sub(1).map(|col| (row, col)),
+                Some((row, col + 1)),
+            ];
+            for neighbor in neighbors.into_iter().flatten() {
+                if cells.contains(&neighbor) && !reached.contains(&neighbor) {
+                    reached.push(neighbor);
+                }
+            }
+        }
+        if reached.len() != cells.len() {
+            return Err(PieceError::NotConnected);
+        }
+
+        Ok(Self { matrix, color, tui_color: color })
+    }
+
     /// Returns the total number of cells occupied by this piece.
     #[must_use]
     pub fn cells(&self) -> u32 {
@@ -20,6 +75,112 @@ impl Piece {
     }
 }
 
+/// Reasons why a shape cannot be made into a piece, as returned by [`Piece::from_shape`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PieceError {
+    /// The shape has no cells.
+    Empty,
+    /// The cells of the shape do not form a single block.
+    NotConnected,
+    /// The color is not a 24-bit RGB value.
+    InvalidColor { color: u32 },
+}
+
+impl fmt::Display for PieceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the piece has no cells"),
+            Self::NotConnected => write!(f, "the cells of the piece are not connected by their sides"),
+            Self::InvalidColor { color } => write!(f, "color {color:#X} is not a 24-bit RGB value"),
+        }
+    }
+}
+
+impl std::error::Error for PieceError {}
+
+
+/// Reasons why a game configuration cannot be solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /// The board has no columns.
+    NoColumns,
+    /// The game has fewer than 2 pieces.
+    NotEnoughPieces { pieces: usize },
+    /// The pieces do not fill complete rows of the board.
+    MissingCells { missing: u32 },
+    /// The board has more cells than the solver supports.
+    BoardTooLarge { cells: u32 },
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoColumns => write!(f, "the board must have at least one column"),
+            Self::NotEnoughPieces { pieces } => write!(f, "at least 2 pieces are needed, got {pieces}"),
+            Self::MissingCells { missing } => write!(f, "pieces are missing to cover {missing} cells"),
+            Self::BoardTooLarge { cells } => {
+                write!(f, "the board has {cells} cells, the maximum is {MAX_BOARD_CELLS}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// Reasons why a board is not a solution of a game, as returned by [`Game::verify`].
+///
+/// Cells are given as `(row, column)`, starting at 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolutionError {
+    /// The game itself cannot be solved.
+    InvalidGame(GameError),
+    /// The board does not have the size of the game board.
+    WrongSize { rows: usize, columns: usize, expected_rows: u32, expected_columns: u32 },
+    /// A cell is not covered by any piece.
+    EmptyCell { row: usize, col: usize },
+    /// A cell holds the id of a piece that is not part of the game.
+    UnknownPiece { row: usize, col: usize, piece: usize },
+    /// A piece of the game is not on the board.
+    MissingPiece { piece: usize },
+    /// The cells of a piece do not match any rotation or reflection of its shape,
+    /// for example because it is split in several parts.
+    WrongShape { piece: usize, cells: Vec<(usize, usize)> },
+}
+
+impl fmt::Display for SolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidGame(error) => write!(f, "invalid game: {error}"),
+            Self::WrongSize { rows, columns, expected_rows, expected_columns } => {
+                write!(f, "the board is {rows}x{columns}, expected {expected_rows}x{expected_columns}")
+            }
+            Self::EmptyCell { row, col } => write!(f, "cell at row {}, column {} is empty", row + 1, col + 1),
+            Self::UnknownPiece { row, col, piece } => {
+                write!(f, "cell at row {}, column {} holds piece {piece}, which is not part of the game", row + 1, col + 1)
+            }
+            Self::MissingPiece { piece } => write!(f, "piece {piece} is not on the board"),
+            Self::WrongShape { piece, cells } => {
+                let cells: Vec<String> = cells.iter().map(|(row, col)| format!("({}, {})", row + 1, col + 1)).collect();
+                write!(f, "piece {piece} does not have its shape, at cells {}", cells.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolutionError {}
+
+/// Where a piece lies on a board, as returned by [`Game::placements`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    /// Index of the piece in [`Game::pieces`].
+    pub piece: usize,
+    /// Index of the orientation of the piece in the [`rotation_variants`] of its matrix.
+    pub orientation: usize,
+    /// Row of the top of the piece.
+    pub row: usize,
+    /// Column of the left of the piece.
+    pub col: usize,
+}
 
 /// Represents a game board configuration with pieces to place.
 ///
@@ -42,6 +203,92 @@ impl Game {
             && self.pieces.len() > 1 
     }
 
+    /// Checks if the game configuration can be solved, explaining why not.
+    ///
+    /// Unlike [`Game::is_valid`], this also rejects boards without columns,
+    /// on which the other methods panic, and boards too large for the solver.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`GameError`] found.
+    pub fn validate(&self) -> Result<(), GameError> {
+        if self.columns == 0 {
+            return Err(GameError::NoColumns);
+        }
+        if self.pieces.len() < 2 {
+            return Err(GameError::NotEnoughPieces { pieces: self.pieces.len() });
+        }
+        if !self.is_valid() {
+            return Err(GameError::MissingCells { missing: self.missing_cells() });
+        }
+        if self.cells() > MAX_BOARD_CELLS {
+            return Err(GameError::BoardTooLarge { cells: self.cells() });
+        }
+        Ok(())
+    }
+
+    /// Checks that `solution` is a solution of the game.
+    ///
+    /// `solution` is encoded like the boards returned by [`crate::GameResolver`]: each cell holds
+    /// the index of its piece in [`Game::pieces`] (see [`crate::game_resolver::with_piece_index`]).
+    /// Every cell must be covered, every piece must be used exactly once, and the cells of each
+    /// piece must form one of its rotations or reflections.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`SolutionError`] found, cells being checked in reading order and pieces by index.
+    pub fn verify(&self, solution: &DMatrix<u32>) -> Result<(), SolutionError> {
+        self.placements(solution).map(|_| ())
+    }
+
+    /// Returns where each piece lies in `solution`, in the order of [`Game::pieces`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Game::verify`], when `solution` is not a solution of the game.
+    pub fn placements(&self, solution: &DMatrix<u32>) -> Result<Vec<Placement>, SolutionError> {
+        self.validate().map_err(SolutionError::InvalidGame)?;
+        let (rows, columns) = solution.shape();
+        if u32::try_from(rows).ok() != Some(self.rows()) || u32::try_from(columns).ok() != Some(self.columns) {
+            return Err(SolutionError::WrongSize { rows, columns, expected_rows: self.rows(), expected_columns: self.columns });
+        }
+
+        let mut cells: Vec<Vec<(usize, usize)>> = vec![Vec::new(); self.pieces.len()];
+        for row in 0..rows {
+            for col in 0..columns {
+                let piece = piece_index(solution[(row, col)]).ok_or(SolutionError::EmptyCell { row, col })?;
+                cells
+                    .get_mut(piece)
+                    .ok_or(SolutionError::UnknownPiece { row, col, piece })?
+                    .push((row, col));
+            }
+        }
+
+        cells
+            .into_iter()
+            .enumerate()
+            .map(|(piece, cells)| {
+                if cells.is_empty() {
+                    return Err(SolutionError::MissingPiece { piece });
+                }
+                let row = cells.iter().map(|&(row, _)| row).min().unwrap_or(0);
+                let col = cells.iter().map(|&(_, col)| col).min().unwrap_or(0);
+                let bottom = cells.iter().map(|&(row, _)| row).max().unwrap_or(0);
+                let right = cells.iter().map(|&(_, col)| col).max().unwrap_or(0);
+                let mut shape = DMatrix::zeros(bottom - row + 1, right - col + 1);
+                for &(cell_row, cell_col) in &cells {
+                    shape[(cell_row - row, cell_col - col)] = 1;
+                }
+                let expected = self.pieces[piece].matrix.map(|cell| u32::from(cell != 0));
+                rotation_variants(&expected)
+                    .iter()
+                    .position(|variant| *variant == shape)
+                    .map(|orientation| Placement { piece, orientation, row, col })
+                    .ok_or(SolutionError::WrongShape { piece, cells })
+            })
+            .collect()
+    }
+
     /// Calculates the number of rows needed for the game board.
     #[must_use]
     pub fn rows(&self) -> u32 {
@@ -117,6 +364,23 @@ mod tests {
         assert_eq!(piece.cells(), 3);
     }
 
+    #[test]
+    fn test_piece_from_shape() {
+        // An L with an empty border row and column
+        let shape = DMatrix::from_row_slice(3, 3, &[0, 0, 0, 2, 0, 0, 1, 1, 0]);
+        let piece = Piece::from_shape(&shape, 0x12_3456).unwrap();
+        assert_eq!(piece.matrix, DMatrix::from_row_slice(2, 2, &[1, 0, 1, 1]));
+        assert_eq!((piece.color, piece.tui_color), (0x12_3456, 0x12_3456));
+
+        assert_eq!(Piece::from_shape(&DMatrix::zeros(2, 2), 0).unwrap_err(), PieceError::Empty);
+        let split = DMatrix::from_row_slice(2, 2, &[1, 0, 0, 1]);
+        assert_eq!(Piece::from_shape(&split, 0).unwrap_err(), PieceError::NotConnected);
+        assert_eq!(
+            Piece::from_shape(&shape, 0x0100_0000).unwrap_err().to_string(),
+            "color 0x1000000 is not a 24-bit RGB value"
+        );
+    }
+
     #[test]
     fn test_game_is_valid() {
         let p1 = create_piece(1, 2, &[1, 1]);
@@ -157,6 +421,83 @@ mod tests {
         assert_eq!(game.missing_cells(), 1);
     }
 
+    #[test]
+    fn test_game_validate() {
+        let bar = create_piece(1, 2, &[1, 1]);
+        let square = create_piece(1, 1, &[1]);
+
+        let game = Game { columns: 2, pieces: vec![bar.clone(), bar.clone()] };
+        assert_eq!(game.validate(), Ok(()));
+
+        let game = Game { columns: 0, pieces: vec![bar.clone(), bar.clone()] };
+        assert_eq!(game.validate(), Err(GameError::NoColumns));
+
+        let game = Game { columns: 2, pieces: vec![bar.clone()] };
+        assert_eq!(game.validate(), Err(GameError::NotEnoughPieces { pieces: 1 }));
+
+        let game = Game { columns: 2, pieces: vec![bar, square.clone()] };
+        assert_eq!(game.validate(), Err(GameError::MissingCells { missing: 1 }));
+
+        let game = Game { columns: 1, pieces: vec![square; 65] };
+        assert_eq!(game.validate(), Err(GameError::BoardTooLarge { cells: 65 }));
+    }
+
+    #[test]
+    fn test_game_verify() {
+        use crate::game_resolver::with_piece_index;
+        use crate::{GameResolver, GameResolverTrait};
+
+        // 2x3 board: a square, a bar and an L
+        let game = Game {
+            columns: 3,
+            pieces: vec![create_piece(1, 1, &[1]), create_piece(1, 2, &[1, 1]), create_piece(2, 2, &[1, 0, 1, 1])],
+        };
+        for solution in GameResolver.resolve(&game) {
+            assert_eq!(game.verify(&solution), Ok(()));
+        }
+
+        let board = |ids: [Option<usize>; 6]| {
+            DMatrix::from_row_slice(2, 3, &ids.map(|id| id.map_or(0, |id| with_piece_index(0x00FF_0000, id))))
+        };
+        let solution = board([Some(0), Some(2), Some(2), Some(1), Some(1), Some(2)]);
+        assert_eq!(game.verify(&solution), Ok(()));
+
+        let empty = board([Some(0), Some(2), Some(2), None, Some(1), Some(2)]);
+        assert_eq!(game.verify(&empty), Err(SolutionError::EmptyCell { row: 1, col: 0 }));
+
+        let unknown = board([Some(0), Some(2), Some(2), Some(5), Some(1), Some(2)]);
+        assert_eq!(game.verify(&unknown), Err(SolutionError::UnknownPiece { row: 1, col: 0, piece: 5 }));
+
+        // The bar is split, so the square is used twice and the bar has the wrong shape
+        let split = board([Some(1), Some(2), Some(2), Some(0), Some(1), Some(2)]);
+        assert_eq!(game.verify(&split), Err(SolutionError::WrongShape { piece: 1, cells: vec![(0, 0), (1, 1)] }));
+        assert_eq!(
+            game.verify(&split).unwrap_err().to_string(),
+            "piece 1 does not have its shape, at cells (1, 1), (2, 2)"
+        );
+
+        assert_eq!(
+            game.placements(&solution),
+            Ok(vec![
+                Placement { piece: 0, orientation: 0, row: 0, col: 0 },
+                Placement { piece: 1, orientation: 0, row: 1, col: 0 },
+                Placement { piece: 2, orientation: 2, row: 0, col: 1 },
+            ])
+        );
+        let variants = rotation_variants(&game.pieces[2].matrix);
+        assert_eq!(variants[2], DMatrix::from_row_slice(2, 2, &[1, 1, 0, 1]));
+
+        let missing = board([Some(1), Some(2), Some(2), Some(1), Some(2), Some(2)]);
+        assert_eq!(game.verify(&missing), Err(SolutionError::MissingPiece { piece: 0 }));
+
+        assert_eq!(
+            game.verify(&DMatrix::zeros(3, 2)),
+            Err(SolutionError::WrongSize { rows: 3, columns: 2, expected_rows: 2, expected_columns: 3 })
+        );
+        let invalid = Game { columns: 4, ..game };
+        assert_eq!(invalid.verify(&solution), Err(SolutionError::InvalidGame(GameError::MissingCells { missing: 2 })));
+    }
+
     #[test]
     fn test_game_from_game() {
         let p1 = create_piece(1, 1, &[1]);
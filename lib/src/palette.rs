@@ -0,0 +1,244 @@
+use crate::game_data::PieceName;
+use crate::models::Game;
+
+// Okabe & Ito colour-blind-safe palette, with grey in place of black so outlines stay visible.
+// Reference: <https://jfly.uni-koeln.de/color/>
+const OKABE_ITO_ORANGE: u32 = 0x00E6_9F00;
+const OKABE_ITO_SKY_BLUE: u32 = 0x0056_B4E9;
+const OKABE_ITO_GREEN: u32 = 0x0000_9E73;
+const OKABE_ITO_YELLOW: u32 = 0x00F0_E442;
+const OKABE_ITO_BLUE: u32 = 0x0000_72B2;
+const OKABE_ITO_VERMILLION: u32 = 0x00D5_5E00;
+const OKABE_ITO_PURPLE: u32 = 0x00CC_79A7;
+const OKABE_ITO_GREY: u32 = 0x0099_9999;
+
+// Paul Tol's "bright" qualitative palette, distinct for all common colour-vision deficiencies.
+// Reference: <https://personal.sron.nl/~pault/>
+const TOL_BLUE: u32 = 0x0044_77AA;
+const TOL_CYAN: u32 = 0x0066_CCEE;
+const TOL_GREEN: u32 = 0x0022_8833;
+const TOL_YELLOW: u32 = 0x00CC_BB44;
+const TOL_RED: u32 = 0x00EE_6677;
+const TOL_PURPLE: u32 = 0x00AA_3377;
+const TOL_GREY: u32 = 0x00BB_BBBB;
+
+// Paul Tol's "high-contrast" palette, plus black and white, for maximum luminance differences.
+// The black is not pure black, as a cell value of 0 means an empty cell.
+const CONTRAST_BLACK: u32 = 0x001A_1A1A;
+const CONTRAST_WHITE: u32 = 0x00FF_FFFF;
+const CONTRAST_YELLOW: u32 = 0x00DD_AA33;
+const CONTRAST_RED: u32 = 0x00BB_5566;
+const CONTRAST_BLUE: u32 = 0x0000_4488;
+
+/// A named set of colors for the game pieces.
+///
+/// Pieces that share a color in [`Palette::Classic`] (such as the two blue or the two pink pieces)
+/// get different colors in the colour-blind-safe palettes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    /// The colors of the wooden pieces of the real game.
+    #[default]
+    Classic,
+    /// The slightly darker variants used by the terminal app, to tell same-colored pieces apart.
+    Terminal,
+    /// The Okabe & Ito colour-blind-safe palette.
+    OkabeIto,
+    /// Paul Tol's "bright" colour-blind-safe palette.
+    TolBright,
+    /// Five colors with strong luminance differences, best combined with [`FillPattern`]s.
+    HighContrast,
+}
+
+impl Palette {
+    /// All palettes, in declaration order.
+    pub const ALL: [Self; 5] = [Self::Classic, Self::Terminal, Self::OkabeIto, Self::TolBright, Self::HighContrast];
+
+    /// Returns the identifier of the palette, as accepted by [`Palette::from_name`].
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+            Self::Terminal => "terminal",
+            Self::OkabeIto => "okabe-ito",
+            Self::TolBright => "tol-bright",
+            Self::HighContrast => "high-contrast",
+        }
+    }
+
+    /// Returns the palette with the given identifier, if any.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|palette| palette.name() == name)
+    }
+
+    /// Returns the color of a piece in this palette, as a 24-bit RGB hex value.
+    #[must_use]
+    pub fn color(self, piece: PieceName) -> u32 {
+        match self {
+            Self::Classic => piece.piece().color,
+            Self::Terminal => piece.piece().tui_color,
+            Self::OkabeIto => match piece {
+                PieceName::RedSquare1 | PieceName::PinkNotSquare5 => OKABE_ITO_VERMILLION,
+                PieceName::TanBar2 | PieceName::YellowZigZag4 | PieceName::OrangeL5 => OKABE_ITO_ORANGE,
+                PieceName::BrownL3 => OKABE_ITO_GREY,
+                PieceName::OrangeBar3 | PieceName::YellowU5 => OKABE_ITO_YELLOW,
+                PieceName::PinkBar4 | PieceName::VioletZigZag5 => OKABE_ITO_PURPLE,
+                PieceName::GreenL4 | PieceName::BrownT5 => OKABE_ITO_GREEN,
+                PieceName::BlueT4 | PieceName::BlueL5 => OKABE_ITO_BLUE,
+                PieceName::VioletSquare4 | PieceName::BlueS5 => OKABE_ITO_SKY_BLUE,
+            },
+            Self::TolBright => match piece {
+                PieceName::RedSquare1 | PieceName::BrownL3 | PieceName::PinkNotSquare5 => TOL_RED,
+                PieceName::TanBar2 | PieceName::YellowZigZag4 | PieceName::OrangeL5 => TOL_YELLOW,
+                PieceName::OrangeBar3 | PieceName::VioletSquare4 | PieceName::BlueS5 => TOL_CYAN,
+                PieceName::PinkBar4 | PieceName::VioletZigZag5 => TOL_PURPLE,
+                PieceName::GreenL4 | PieceName::YellowU5 => TOL_GREEN,
+                PieceName::BlueT4 | PieceName::BlueL5 => TOL_BLUE,
+                PieceName::BrownT5 => TOL_GREY,
+            },
+            Self::HighContrast => match piece {
+                PieceName::RedSquare1 | PieceName::TanBar2 | PieceName::GreenL4
+                | PieceName::BrownT5 | PieceName::BlueS5 => CONTRAST_RED,
+                PieceName::BrownL3 | PieceName::PinkBar4 | PieceName::OrangeL5 | PieceName::YellowU5 => CONTRAST_YELLOW,
+                PieceName::OrangeBar3 | PieceName::BlueT4 | PieceName::VioletZigZag5 => CONTRAST_BLUE,
+                PieceName::YellowZigZag4 | PieceName::BlueL5 => CONTRAST_BLACK,
+                PieceName::VioletSquare4 | PieceName::PinkNotSquare5 => CONTRAST_WHITE,
+            },
+        }
+    }
+
+    /// Recolors the pieces of `game` with this palette.
+    ///
+    /// Pieces are recognized by their shape with [`PieceName::from_matrix`];
+    /// pieces that are not part of the original game keep their colors.
+    pub fn apply(self, game: &mut Game) {
+        for piece in &mut game.pieces {
+            if let Some(name) = PieceName::from_matrix(&piece.matrix) {
+                piece.color = self.color(name);
+                piece.tui_color = piece.color;
+            }
+        }
+    }
+}
+
+/// A pattern drawn over the color of a piece, so that pieces can be told apart without colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillPattern {
+    /// No pattern, only the color.
+    Solid,
+    /// Diagonal lines going up to the right.
+    DiagonalUp,
+    /// Diagonal lines going down to the right.
+    DiagonalDown,
+    /// Horizontal lines.
+    Horizontal,
+    /// Vertical lines.
+    Vertical,
+    /// Both diagonals.
+    Cross,
+    /// Horizontal and vertical lines.
+    Grid,
+    /// Regularly spaced dots.
+    Dots,
+}
+
+impl FillPattern {
+    /// Returns the pattern of a piece.
+    ///
+    /// Pieces of the same size, and pieces sharing a color in [`Palette::Classic`],
+    /// always have different patterns.
+    #[must_use]
+    pub const fn for_piece(piece: PieceName) -> Self {
+        match piece {
+            PieceName::RedSquare1 | PieceName::BlueS5 => Self::Solid,
+            PieceName::BrownL3 | PieceName::VioletZigZag5 => Self::DiagonalUp,
+            PieceName::BlueT4 | PieceName::PinkNotSquare5 => Self::DiagonalDown,
+            PieceName::OrangeBar3 | PieceName::BlueL5 => Self::Horizontal,
+            PieceName::PinkBar4 | PieceName::OrangeL5 => Self::Vertical,
+            PieceName::GreenL4 | PieceName::YellowU5 => Self::Cross,
+            PieceName::YellowZigZag4 | PieceName::BrownT5 => Self::Grid,
+            PieceName::TanBar2 | PieceName::VioletSquare4 => Self::Dots,
+        }
+    }
+
+    /// Returns the identifier of the pattern, used to name its SVG definition.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Solid => "solid",
+            Self::DiagonalUp => "diagonal-up",
+            Self::DiagonalDown => "diagonal-down",
+            Self::Horizontal => "horizontal",
+            Self::Vertical => "vertical",
+            Self::Cross => "cross",
+            Self::Grid => "grid",
+            Self::Dots => "dots",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pieces of the same color in the classic palette.
+    const CLASSIC_LOOKALIKES: [(PieceName, PieceName); 6] = [
+        (PieceName::BrownL3, PieceName::BrownT5),
+        (PieceName::OrangeBar3, PieceName::OrangeL5),
+        (PieceName::PinkBar4, PieceName::PinkNotSquare5),
+        (PieceName::BlueT4, PieceName::BlueS5),
+        (PieceName::YellowZigZag4, PieceName::YellowU5),
+        (PieceName::VioletSquare4, PieceName::VioletZigZag5),
+    ];
+
+    #[test]
+    fn test_from_name() {
+        for palette in Palette::ALL {
+            assert_eq!(Palette::from_name(palette.name()), Some(palette));
+        }
+        assert_eq!(Palette::from_name("rainbow"), None);
+    }
+
+    #[test]
+    fn test_colour_blind_palettes_split_lookalikes() {
+        for palette in [Palette::OkabeIto, Palette::TolBright, Palette::HighContrast] {
+            for (a, b) in CLASSIC_LOOKALIKES {
+                assert_ne!(palette.color(a), palette.color(b), "{a:?} and {b:?} in {palette:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_patterns_split_lookalikes_and_sizes() {
+        for (a, b) in CLASSIC_LOOKALIKES {
+            assert_ne!(FillPattern::for_piece(a), FillPattern::for_piece(b));
+        }
+        for a in PieceName::ALL {
+            for b in PieceName::ALL {
+                if a != b && a.piece().cells() == b.piece().cells() {
+                    assert_ne!(FillPattern::for_piece(a), FillPattern::for_piece(b), "{a:?} and {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_piece_is_invisible() {
+        for palette in Palette::ALL {
+            for name in PieceName::ALL {
+                let color = palette.color(name);
+                assert_ne!(color, 0, "{name:?} in {palette:?}");
+                let svg = crate::svg_renderer::svg_from_matrix(&(name.piece().matrix * color));
+                assert!(svg.contains("<rect"), "{name:?} in {palette:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut game = Game::game_with_all_pieces();
+        Palette::OkabeIto.apply(&mut game);
+        assert_eq!(game.pieces[0].color, OKABE_ITO_VERMILLION);
+        assert_eq!(game.pieces[17].color, OKABE_ITO_SKY_BLUE);
+    }
+}
@@ -0,0 +1,503 @@
+//! A game being played: a partial board on which the player puts and takes back pieces.
+
+use std::fmt;
+
+use nalgebra::DMatrix;
+
+use crate::bitboard::{collides, fits, matrix_to_bitboard, BitBoard};
+use crate::game_resolver::with_piece_index;
+use crate::hints::{Hint, HintEngine, HintLevel};
+use crate::matrix_tools::{flip_matrix, rotate_matrix, rotation_variants};
+use crate::models::{Game, GameError, Placement};
+
+/// What an action of a [`PlaySession`] changed on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayEvent {
+    /// A piece was put on the board.
+    Placed(Placement),
+    /// A piece was taken off the board, and is now held.
+    Removed(Placement),
+    /// The last piece was put on the board, which is now a solution of the game.
+    Completed(Placement),
+}
+
+/// Reasons why an action of a [`PlaySession`] is refused.
+///
+/// Rows and columns are displayed starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayError {
+    /// The piece index is not in [`Game::pieces`].
+    UnknownPiece { piece: usize },
+    /// The orientation index is not one of the orientations of the piece.
+    UnknownOrientation { piece: usize, orientation: usize, orientations: usize },
+    /// The piece is already on the board.
+    AlreadyPlaced { piece: usize },
+    /// The piece is not on the board.
+    NotPlaced { piece: usize },
+    /// The piece would stick out of the board.
+    OutOfBounds { piece: usize, row: usize, col: usize },
+    /// The piece would cover cells of another piece.
+    Overlaps { piece: usize, other: usize },
+    /// No piece is held.
+    NothingHeld,
+    NothingToUndo,
+    NothingToRedo,
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPiece { piece } => write!(f, "piece {piece} is not part of the game"),
+            Self::UnknownOrientation { piece, orientation, orientations } => {
+                write!(f, "piece {piece} has {orientations} orientations, got orientation {orientation}")
+            }
+            Self::AlreadyPlaced { piece } => write!(f, "piece {piece} is already on the board"),
+            Self::NotPlaced { piece } => write!(f, "piece {piece} is not on the board"),
+            Self::OutOfBounds { piece, row, col } => {
+                write!(f, "piece {piece} does not fit on the board at row {}, column {}", row + 1, col + 1)
+            }
+            Self::Overlaps { piece, other } => write!(f, "piece {piece} overlaps piece {other}"),
+            Self::NothingHeld => write!(f, "no piece is held"),
+            Self::NothingToUndo => write!(f, "nothing to undo"),
+            Self::NothingToRedo => write!(f, "nothing to redo"),
+        }
+    }
+}
+
+impl std::error::Error for PlayError {}
+
+/// A piece on the board, with its cells.
+#[derive(Debug, Clone, Copy)]
+struct Placed {
+    placement: Placement,
+    bits: BitBoard,
+}
+
+/// An action of the history, undone by applying the opposite action.
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Place(Placement),
+    Remove(Placement),
+}
+
+impl Action {
+    const fn opposite(self) -> Self {
+        match self {
+            Self::Place(placement) => Self::Remove(placement),
+            Self::Remove(placement) => Self::Place(placement),
+        }
+    }
+}
+
+/// A game being played: the pieces on the board, the piece in hand, and the history of the board.
+///
+/// Pieces are put on the board with [`PlaySession::place`], which checks that they fit within
+/// the board and do not overlap other pieces, and taken back with [`PlaySession::remove`].
+/// Both can be undone and redone. Orientations are indices in the [`rotation_variants`] of the
+/// pieces, as in [`Placement::orientation`].
+///
+/// The player can also hold a piece, to turn it with [`PlaySession::rotate`] and
+/// [`PlaySession::flip`] before placing it with [`PlaySession::place_held`]. Holding and turning
+/// pieces are not part of the history.
+pub struct PlaySession {
+    game: Game,
+    rows: usize,
+    cols: usize,
+    /// Orientations of each piece, cells being 1.
+    orientations: Vec<Vec<DMatrix<u32>>>,
+    /// Pieces on the board, in the order they were placed.
+    placed: Vec<Placed>,
+    /// Held piece and its orientation.
+    held: Option<(usize, usize)>,
+    undo: Vec<Action>,
+    redo: Vec<Action>,
+}
+
+impl PlaySession {
+    /// Starts playing `game` on an empty board.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`GameError`] of the game when it cannot be solved, see [`Game::validate`].
+    pub fn new(game: Game) -> Result<Self, GameError> {
+        game.validate()?;
+        let rows = game.rows() as usize;
+        let cols = game.columns as usize;
+        let orientations = game
+            .pieces
+            .iter()
+            .map(|piece| rotation_variants(&piece.matrix.map(|cell| u32::from(cell != 0))))
+            .collect();
+        Ok(Self { game, rows, cols, orientations, placed: Vec::new(), held: None, undo: Vec::new(), redo: Vec::new() })
+    }
+
+    #[must_use]
+    pub const fn game(&self) -> &Game {
+        &self.game
+    }
+
+    #[must_use]
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[must_use]
+    pub const fn columns(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the shapes of the orientations of `piece`, cells being 1.
+    #[must_use]
+    pub fn orientations(&self, piece: usize) -> Option<&[DMatrix<u32>]> {
+        self.orientations.get(piece).map(Vec::as_slice)
+    }
+
+    /// Returns the pieces on the board, in the order they were placed.
+    #[must_use]
+    pub fn placements(&self) -> Vec<Placement> {
+        self.placed.iter().map(|placed| placed.placement).collect()
+    }
+
+    #[must_use]
+    pub fn placement(&self, piece: usize) -> Option<Placement> {
+        self.placed.iter().map(|placed| placed.placement).find(|placement| placement.piece == piece)
+    }
+
+    #[must_use]
+    pub fn is_placed(&self, piece: usize) -> bool {
+        self.placement(piece).is_some()
+    }
+
+    /// Returns the indices of the pieces not on the board.
+    #[must_use]
+    pub fn remaining(&self) -> Vec<usize> {
+        (0..self.game.pieces.len()).filter(|&piece| !self.is_placed(piece)).collect()
+    }
+
+    /// Whether every piece is on the board, which is then a solution of the game.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.placed.len() == self.game.pieces.len()
+    }
+
+    /// Returns the cells covered by pieces.
+    #[must_use]
+    pub fn occupied(&self) -> BitBoard {
+        self.placed.iter().fold(0, |bits, placed| bits | placed.bits)
+    }
+
+    /// Returns a hint for the board giving away as much as `level`, see [`HintEngine`].
+    #[must_use]
+    pub fn hint(&self, level: HintLevel) -> Hint {
+        // The game was validated by `PlaySession::new`, and the pieces were placed on its board
+        let mut cells = vec![0; self.game.pieces.len()];
+        for placed in &self.placed {
+            cells[placed.placement.piece] = placed.bits;
+        }
+        HintEngine::from_cells(&self.game, self.board(), cells).hint(level)
+    }
+
+    /// Returns the pieces on the board, encoded like the solutions of the solver
+    /// (see [`with_piece_index`]), with 0 for empty cells.
+    #[must_use]
+    pub fn board(&self) -> DMatrix<u32> {
+        let mut board = DMatrix::zeros(self.rows, self.cols);
+        for placed in &self.placed {
+            let Placement { piece, orientation, row, col } = placed.placement;
+            let value = with_piece_index(self.game.pieces[piece].color, piece);
+            let shape = &self.orientations[piece][orientation];
+            for r in 0..shape.nrows() {
+                for c in 0..shape.ncols() {
+                    if shape[(r, c)] != 0 {
+                        board[(row + r, col + c)] = value;
+                    }
+                }
+            }
+        }
+        board
+    }
+
+    /// Returns the held piece and its orientation.
+    #[must_use]
+    pub const fn held(&self) -> Option<(usize, usize)> {
+        self.held
+    }
+
+    /// Takes `piece` in hand, in `orientation`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PlayError`] when the piece or the orientation is unknown, or the piece is on the board.
+    pub fn hold(&mut self, piece: usize, orientation: usize) -> Result<(), PlayError> {
+        self.check_orientation(piece, orientation)?;
+        if self.is_placed(piece) {
+            return Err(PlayError::AlreadyPlaced { piece });
+        }
+        self.held = Some((piece, orientation));
+        Ok(())
+    }
+
+    /// Puts the held piece down, next to the board.
+    pub const fn release(&mut self) {
+        self.held = None;
+    }
+
+    /// Turns the held piece a quarter turn clockwise, returning its new orientation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlayError::NothingHeld`] when no piece is held.
+    pub fn rotate(&mut self) -> Result<usize, PlayError> {
+        self.reorient(rotate_matrix)
+    }
+
+    /// Turns the held piece over, mirroring it left to right, returning its new orientation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlayError::NothingHeld`] when no piece is held.
+    pub fn flip(&mut self) -> Result<usize, PlayError> {
+        self.reorient(flip_matrix)
+    }
+
+    fn reorient(&mut self, transform: fn(&DMatrix<u32>) -> DMatrix<u32>) -> Result<usize, PlayError> {
+        let (piece, orientation) = self.held.ok_or(PlayError::NothingHeld)?;
+        let orientations = &self.orientations[piece];
+        let shape = transform(&orientations[orientation]);
+        // The orientations hold every rotation and reflection of the piece
+        let orientation = orientations.iter().position(|variant| *variant == shape).expect("Orientations are closed under rotations and reflections");
+        self.held = Some((piece, orientation));
+        Ok(orientation)
+    }
+
+    /// Puts `piece` on the board in `orientation`, with its top left corner at `row` and `col`.
+    ///
+    /// The piece is no longer held, and the redo history is cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PlayError`] when the piece or the orientation is unknown, the piece is already
+    /// on the board, sticks out of the board, or overlaps another piece.
+    pub fn place(&mut self, piece: usize, orientation: usize, row: usize, col: usize) -> Result<PlayEvent, PlayError> {
+        let placement = Placement { piece, orientation, row, col };
+        self.check_orientation(piece, orientation)?;
+        if self.is_placed(piece) {
+            return Err(PlayError::AlreadyPlaced { piece });
+        }
+        let shape = &self.orientations[piece][orientation];
+        if !fits(shape, self.rows, self.cols, row, col) {
+            return Err(PlayError::OutOfBounds { piece, row, col });
+        }
+        let bits = matrix_to_bitboard(shape, self.rows, self.cols, row, col);
+        if let Some(other) = self.placed.iter().find(|placed| collides(placed.bits, bits)) {
+            return Err(PlayError::Overlaps { piece, other: other.placement.piece });
+        }
+        Ok(self.perform(Action::Place(placement)))
+    }
+
+    /// Puts the held piece on the board in its orientation, as [`PlaySession::place`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlayError::NothingHeld`] when no piece is held, or the errors of [`PlaySession::place`].
+    pub fn place_held(&mut self, row: usize, col: usize) -> Result<PlayEvent, PlayError> {
+        let (piece, orientation) = self.held.ok_or(PlayError::NothingHeld)?;
+        self.place(piece, orientation, row, col)
+    }
+
+    /// Takes `piece` off the board and holds it, in its orientation.
+    ///
+    /// The redo history is cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PlayError`] when the piece is unknown or not on the board.
+    pub fn remove(&mut self, piece: usize) -> Result<PlayEvent, PlayError> {
+        if piece >= self.game.pieces.len() {
+            return Err(PlayError::UnknownPiece { piece });
+        }
+        let placement = self.placement(piece).ok_or(PlayError::NotPlaced { piece })?;
+        Ok(self.perform(Action::Remove(placement)))
+    }
+
+    #[must_use]
+    pub const fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    #[must_use]
+    pub const fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Cancels the last placement or removal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlayError::NothingToUndo`] at the start of the history.
+    pub fn undo(&mut self) -> Result<PlayEvent, PlayError> {
+        let action = self.undo.pop().ok_or(PlayError::NothingToUndo)?;
+        self.redo.push(action);
+        Ok(self.apply(action.opposite()))
+    }
+
+    /// Performs again the last action cancelled by [`PlaySession::undo`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlayError::NothingToRedo`] when no action was undone since the last one.
+    pub fn redo(&mut self) -> Result<PlayEvent, PlayError> {
+        let action = self.redo.pop().ok_or(PlayError::NothingToRedo)?;
+        self.undo.push(action);
+        Ok(self.apply(action))
+    }
+
+    fn check_orientation(&self, piece: usize, orientation: usize) -> Result<(), PlayError> {
+        let orientations = self.orientations.get(piece).ok_or(PlayError::UnknownPiece { piece })?.len();
+        if orientation >= orientations {
+            return Err(PlayError::UnknownOrientation { piece, orientation, orientations });
+        }
+        Ok(())
+    }
+
+    /// Applies a new action, which replaces the actions that could be redone.
+    fn perform(&mut self, action: Action) -> PlayEvent {
+        self.undo.push(action);
+        self.redo.clear();
+        self.apply(action)
+    }
+
+    /// Applies an action checked when it was first performed.
+    ///
+    /// Actions are undone and redone in reverse order, so they remain valid.
+    fn apply(&mut self, action: Action) -> PlayEvent {
+        match action {
+            Action::Place(placement) => {
+                let shape = &self.orientations[placement.piece][placement.orientation];
+                let bits = matrix_to_bitboard(shape, self.rows, self.cols, placement.row, placement.col);
+                self.placed.push(Placed { placement, bits });
+                if self.held.is_some_and(|(piece, _)| piece == placement.piece) {
+                    self.held = None;
+                }
+                if self.is_complete() { PlayEvent::Completed(placement) } else { PlayEvent::Placed(placement) }
+            }
+            Action::Remove(placement) => {
+                self.placed.retain(|placed| placed.placement.piece != placement.piece);
+                self.held = Some((placement.piece, placement.orientation));
+                PlayEvent::Removed(placement)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::PieceName;
+    use crate::game_resolver::piece_index;
+
+    /// A 2x2 board with a red square and a brown L.
+    fn session() -> PlaySession {
+        PlaySession::new(Game { columns: 2, pieces: vec![PieceName::RedSquare1.piece(), PieceName::BrownL3.piece()] }).unwrap()
+    }
+
+    /// Orientation of the L with its missing cell at the top left.
+    fn l_orientation(session: &PlaySession) -> usize {
+        let shape = DMatrix::from_row_slice(2, 2, &[0, 1, 1, 1]);
+        session.orientations(1).unwrap().iter().position(|variant| *variant == shape).unwrap()
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_games() {
+        let game = Game { columns: 3, pieces: vec![PieceName::RedSquare1.piece(), PieceName::BrownL3.piece()] };
+        assert_eq!(PlaySession::new(game).err(), Some(GameError::MissingCells { missing: 2 }));
+    }
+
+    #[test]
+    fn test_place_and_complete() {
+        let mut session = session();
+        let square = Placement { piece: 0, orientation: 0, row: 0, col: 0 };
+        assert_eq!(session.place(0, 0, 0, 0), Ok(PlayEvent::Placed(square)));
+        assert_eq!(session.remaining(), [1]);
+
+        let orientation = l_orientation(&session);
+        let l = Placement { piece: 1, orientation, row: 0, col: 0 };
+        assert_eq!(session.hint(HintLevel::Placement), Hint::Placement(l));
+        // The session builds the same engine as one reading its board
+        let engine = HintEngine::new(session.game(), &session.board()).unwrap();
+        assert_eq!(engine.hint(HintLevel::Placement), session.hint(HintLevel::Placement));
+        assert_eq!(session.place(1, orientation, 0, 0), Ok(PlayEvent::Completed(l)));
+        assert!(session.is_complete());
+        assert_eq!(session.game().verify(&session.board()), Ok(()));
+        assert_eq!(session.placements(), [square, l]);
+        assert_eq!(session.hint(HintLevel::Solvable), Hint::Complete);
+    }
+
+    #[test]
+    fn test_place_checks() {
+        let mut session = session();
+        assert_eq!(session.place(2, 0, 0, 0), Err(PlayError::UnknownPiece { piece: 2 }));
+        assert_eq!(session.place(0, 1, 0, 0), Err(PlayError::UnknownOrientation { piece: 0, orientation: 1, orientations: 1 }));
+        assert_eq!(session.place(0, 0, 2, 0), Err(PlayError::OutOfBounds { piece: 0, row: 2, col: 0 }));
+        assert_eq!(session.place(1, 0, 0, 1), Err(PlayError::OutOfBounds { piece: 1, row: 0, col: 1 }));
+
+        session.place(0, 0, 0, 0).unwrap();
+        assert_eq!(session.place(0, 0, 1, 1), Err(PlayError::AlreadyPlaced { piece: 0 }));
+        // Every orientation of the L but one covers the top left cell
+        let overlapping = (0..4).find(|&orientation| orientation != l_orientation(&session)).unwrap();
+        assert_eq!(session.place(1, overlapping, 0, 0), Err(PlayError::Overlaps { piece: 1, other: 0 }));
+        assert_eq!(session.placements().len(), 1);
+        assert_eq!(PlayError::OutOfBounds { piece: 0, row: 2, col: 0 }.to_string(), "piece 0 does not fit on the board at row 3, column 1");
+    }
+
+    #[test]
+    fn test_hold_rotate_and_flip() {
+        let mut session = session();
+        assert_eq!(session.rotate(), Err(PlayError::NothingHeld));
+        session.hold(1, 0).unwrap();
+
+        // Four quarter turns, or two flips, give the same orientation back
+        let turns: Vec<usize> = (0..4).map(|_| session.rotate().unwrap()).collect();
+        assert_eq!(turns.len(), 4);
+        assert_eq!(turns[3], 0);
+        assert_ne!(turns[0], 0);
+        let flipped = session.flip().unwrap();
+        assert_ne!(flipped, 0);
+        assert_eq!(session.flip(), Ok(0));
+
+        while session.held() != Some((1, l_orientation(&session))) {
+            session.rotate().unwrap();
+        }
+        session.place_held(0, 0).unwrap();
+        assert_eq!(session.held(), None);
+        assert_eq!(session.hold(1, 0), Err(PlayError::AlreadyPlaced { piece: 1 }));
+        assert_eq!(session.place_held(0, 0), Err(PlayError::NothingHeld));
+    }
+
+    #[test]
+    fn test_remove_undo_redo() {
+        let mut session = session();
+        assert_eq!(session.undo(), Err(PlayError::NothingToUndo));
+        let orientation = l_orientation(&session);
+        session.place(1, orientation, 0, 0).unwrap();
+        session.place(0, 0, 0, 0).unwrap();
+
+        let l = Placement { piece: 1, orientation, row: 0, col: 0 };
+        assert_eq!(session.remove(1), Ok(PlayEvent::Removed(l)));
+        assert_eq!(session.held(), Some((1, orientation)));
+        assert_eq!(session.remove(1), Err(PlayError::NotPlaced { piece: 1 }));
+
+        // Undoing the removal completes the board again
+        assert_eq!(session.undo(), Ok(PlayEvent::Completed(l)));
+        assert_eq!(session.held(), None);
+        assert!(matches!(session.undo(), Ok(PlayEvent::Removed(Placement { piece: 0, .. }))));
+        assert_eq!(session.board().iter().filter(|&&cell| piece_index(cell) == Some(1)).count(), 3);
+        assert!(session.can_redo());
+
+        assert!(matches!(session.redo(), Ok(PlayEvent::Completed(Placement { piece: 0, .. }))));
+        // A new action replaces the actions that could be redone
+        session.remove(0).unwrap();
+        assert!(!session.can_redo());
+        assert_eq!(session.redo(), Err(PlayError::NothingToRedo));
+    }
+}
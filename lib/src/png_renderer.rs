@@ -0,0 +1,94 @@
+use std::fmt;
+
+use nalgebra::DMatrix;
+use resvg::{tiny_skia, usvg};
+
+use crate::svg_renderer::svg_from_matrix;
+
+/// Errors that can occur while rasterizing an SVG document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PngError {
+    /// The SVG document could not be parsed.
+    InvalidSvg(String),
+    /// The scaled image has no pixels, or is too large to be allocated.
+    InvalidSize { width: u32, height: u32 },
+    /// The rendered pixels could not be encoded as PNG.
+    Encoding(String),
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSvg(reason) => write!(f, "invalid SVG document: {reason}"),
+            Self::InvalidSize { width, height } => write!(f, "invalid image size: {width}x{height}"),
+            Self::Encoding(reason) => write!(f, "PNG encoding failed: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for PngError {}
+
+/// Rasterizes an SVG document into PNG bytes.
+///
+/// System fonts are loaded so that text, such as contact-sheet labels, is rendered.
+///
+/// # Arguments
+///
+/// * `svg` - An SVG document, typically produced by [`crate::svg_renderer`]
+/// * `scale` - Number of pixels per SVG user unit (a board cell is 10 units wide)
+///
+/// # Errors
+///
+/// Returns a [`PngError`] if the document cannot be parsed, is empty, or cannot be encoded.
+pub fn png_from_svg(svg: &str, scale: f32) -> Result<Vec<u8>, PngError> {
+    let mut options = usvg::Options::default();
+    let fontdb = options.fontdb_mut();
+    fontdb.load_system_fonts();
+
+    // The generic sans-serif family defaults to Arial, which many systems lack:
+    // fall back to the first installed font so that labels are still rendered.
+    let sans_serif = usvg::fontdb::Query { families: &[usvg::fontdb::Family::SansSerif], ..Default::default() };
+    if fontdb.query(&sans_serif).is_none() {
+        let fallback = fontdb.faces().find_map(|face| face.families.first()).map(|(family, _)| family.clone());
+        if let Some(family) = fallback {
+            fontdb.set_sans_serif_family(family);
+        }
+    }
+
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|err| PngError::InvalidSvg(err.to_string()))?;
+    let size = tree.size().to_int_size().scale_by(scale);
+    let (width, height) = size.map_or((0, 0), |size| (size.width(), size.height()));
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or(PngError::InvalidSize { width, height })?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+
+    pixmap.encode_png().map_err(|err| PngError::Encoding(err.to_string()))
+}
+
+/// Rasterizes a board matrix, rendered like [`svg_from_matrix`], into PNG bytes.
+///
+/// # Errors
+///
+/// Returns a [`PngError`] if the scaled image cannot be allocated or encoded.
+pub fn png_from_matrix(matrix: &DMatrix<u32>, scale: f32) -> Result<Vec<u8>, PngError> {
+    png_from_svg(&svg_from_matrix(matrix), scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G'];
+
+    #[test]
+    fn test_png_from_matrix() {
+        let matrix = DMatrix::from_row_slice(1, 2, &[0xFF_0000, 0x00_FF00]);
+        let png = png_from_matrix(&matrix, 2.0).unwrap();
+        assert!(png.starts_with(PNG_SIGNATURE));
+    }
+
+    #[test]
+    fn test_png_from_invalid_svg() {
+        assert!(matches!(png_from_svg("not svg", 1.0), Err(PngError::InvalidSvg(_))));
+    }
+}
@@ -1,6 +1,69 @@
+use std::fmt::Write;
+
 use nalgebra::DMatrix;
 use svg::Document;
-use svg::node::element::{Line, Rectangle};
+use svg::Node;
+use svg::node::element::{Circle, Definitions, Group, Line, Path, Pattern, Rectangle, Style, Text, Title};
+
+use crate::assembly::{assembly_steps, AssemblyOrder};
+use crate::game_data::PieceName;
+use crate::game_resolver::piece_index;
+use crate::models::Game;
+use crate::palette::FillPattern;
+
+/// Size of a board cell, in SVG user units.
+const CELL_SIZE: usize = 10;
+
+const BORDER_COLOR: &str = "black";
+const BORDER_OPACITY: f32 = 0.3;
+const BORDER_WIDTH: f32 = 0.5;
+
+/// CSS class shared by all piece outlines in multi-board documents.
+const BORDER_CLASS: &str = "gtp-border";
+/// CSS class shared by all index labels in multi-board documents.
+const LABEL_CLASS: &str = "gtp-label";
+/// Height reserved under each board for its index label, in SVG user units.
+const LABEL_HEIGHT: usize = 8;
+/// CSS class of the empty board drawn under animated pieces.
+const BOARD_CLASS: &str = "gtp-board";
+/// CSS class of the group of each piece of a board.
+const PIECE_CLASS: &str = "gtp-piece";
+/// CSS class of each piece group of an animated assembly.
+const STEP_CLASS: &str = "gtp-step";
+/// CSS class of the outline of a challenge card.
+const CARD_CLASS: &str = "gtp-card";
+/// CSS class of the grid lines of an empty board.
+const GRID_CLASS: &str = "gtp-grid";
+/// CSS class of the board dimensions printed on a challenge card.
+const DIMENSIONS_CLASS: &str = "gtp-dimensions";
+/// Space between the edge of a challenge card and its content, in SVG user units.
+const CARD_MARGIN: usize = 10;
+/// Minimum width of the content of a challenge card, in cells.
+const CARD_MIN_COLUMNS: usize = 8;
+/// Height reserved under the board of a challenge card for its dimensions.
+const CARD_TEXT_HEIGHT: usize = 16;
+/// Space between two piece silhouettes on a challenge card, in SVG user units.
+const CARD_PIECE_GAP: usize = 5;
+
+/// How piece outlines get their stroke attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BorderStyle {
+    /// Each line carries its own stroke attributes (standalone documents).
+    Inline,
+    /// Each line references [`BORDER_CLASS`], defined once in the document `<defs>`.
+    Shared,
+}
+
+/// Decorations that help telling pieces apart without relying on their colors.
+///
+/// Decorations are only drawn on pieces recognized by [`PieceName::from_matrix`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SvgOptions {
+    /// Draws the [`FillPattern`] of each piece over its color.
+    pub patterns: bool,
+    /// Prints the [`PieceName::letter`] of each piece on one of its cells.
+    pub letters: bool,
+}
 
 /// Converts a matrix representation of a game board to an SVG string.
 ///
@@ -8,6 +71,26 @@ use svg::node::element::{Line, Rectangle};
 /// Non-zero values are rendered with their RGB color, zero values are transparent.
 /// Borders are drawn only around the outline of each piece (connected cells of the same color).
 ///
+/// # Structure
+///
+/// Every piece is a `<g class="gtp-piece">` holding its cells, outline and decorations,
+/// so that the frontend can style or script a whole piece at once:
+///
+/// - `data-piece-id`: index of the piece in [`Game::pieces`], as encoded by [`crate::GameResolver`]
+///   (see [`piece_index`]). It is the same in every solution of a game, and matches the id of
+///   the piece in the piece list. Omitted for cells without an encoded piece id.
+/// - `data-piece-name`: the [`PieceName`] variant, such as `BlueT4`, when the shape is recognized.
+/// - a `<title>` child describing the piece, such as `Blue T, 4 cells`, for screen readers and tooltips.
+///
+/// ```svg
+/// <g class="gtp-piece" data-piece-id="8" data-piece-name="BlueT4">
+///   <title>Blue T, 4 cells</title>
+///   <rect .../> <line .../>
+/// </g>
+/// ```
+///
+/// The same groups are used by every renderer of this module.
+///
 /// # Arguments
 ///
 /// * `matrix` - A matrix where each value represents a color (24-bit RGB hex)
@@ -17,95 +100,596 @@ use svg::node::element::{Line, Rectangle};
 /// An SVG document as a string
 #[must_use]
 pub fn svg_from_matrix(matrix: &DMatrix<u32>) -> String {
-    let width = matrix.ncols();
-    let height = matrix.nrows();
+    svg_from_matrix_with_options(matrix, &SvgOptions::default())
+}
+
+/// Converts a matrix representation of a game board to an SVG string, with decorations.
+///
+/// Same as [`svg_from_matrix`], with the pattern fills and piece letters selected in `options`.
+#[must_use]
+pub fn svg_from_matrix_with_options(matrix: &DMatrix<u32>, options: &SvgOptions) -> String {
+    let mut document = Document::new()
+        .set("viewBox", (0, 0, matrix.ncols() * CELL_SIZE, matrix.nrows() * CELL_SIZE));
+    if options.patterns {
+        document = document.add(add_pattern_definitions(Definitions::new()));
+    }
+
+    add_board(document, matrix, BorderStyle::Inline, *options, |_| true).to_string()
+}
+
+/// Layout options for [`svg_contact_sheet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContactSheetOptions {
+    /// Number of boards per row of the sheet (at least 1).
+    pub columns: usize,
+    /// Space between two boards, in SVG user units (a board cell is 10 units wide).
+    pub gap: usize,
+    /// Whether to print the index of each solution under its board.
+    pub labels: bool,
+    /// Index printed under the first board, when `labels` is enabled.
+    pub first_label: usize,
+    /// Decorations drawn on every board.
+    pub style: SvgOptions,
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        Self {
+            columns: 4,
+            gap: CELL_SIZE,
+            labels: true,
+            first_label: 1,
+            style: SvgOptions::default(),
+        }
+    }
+}
+
+/// Lays out several boards in a grid inside a single SVG document.
+///
+/// Boards are placed left to right, top to bottom, `options.columns` per row.
+/// Every board is rendered like [`svg_from_matrix`] inside its own translated `<g>`,
+/// but the outline and label styles are declared once in a shared `<defs>` block
+/// to keep the document small when rendering hundreds of solutions.
+///
+/// # Arguments
+///
+/// * `matrices` - The boards to render, typically the solutions of a game
+/// * `options` - Grid layout and labelling options
+///
+/// # Returns
+///
+/// An SVG document as a string
+#[must_use]
+pub fn svg_contact_sheet(matrices: &[DMatrix<u32>], options: &ContactSheetOptions) -> String {
+    let columns = options.columns.max(1);
+    let rows = matrices.len().div_ceil(columns);
+    let board_width = matrices.iter().map(DMatrix::ncols).max().unwrap_or(0) * CELL_SIZE;
+    let board_height = matrices.iter().map(DMatrix::nrows).max().unwrap_or(0) * CELL_SIZE;
+    let label_height = if options.labels { LABEL_HEIGHT } else { 0 };
+
+    let tile_width = board_width + options.gap;
+    let tile_height = board_height + label_height + options.gap;
+    let sheet_columns = columns.min(matrices.len());
+    let width = (sheet_columns * tile_width).saturating_sub(options.gap);
+    let height = (rows * tile_height).saturating_sub(options.gap);
+
+    let style = format!(
+        "{}.{LABEL_CLASS}{{font-family:sans-serif;font-size:6px;text-anchor:middle;fill:#333}}",
+        border_css()
+    );
+
+    let mut definitions = Definitions::new().add(Style::new(style));
+    if options.style.patterns {
+        definitions = add_pattern_definitions(definitions);
+    }
+    let mut document = Document::new()
+        .set("viewBox", (0, 0, width, height))
+        .add(definitions);
+
+    for (index, matrix) in matrices.iter().enumerate() {
+        let x = (index % columns) * tile_width;
+        let y = (index / columns) * tile_height;
+
+        let mut board = add_board(Group::new(), matrix, BorderStyle::Shared, options.style, |_| true)
+            .set("transform", format!("translate({x},{y})"));
+
+        if options.labels {
+            let label = Text::new()
+                .set("class", LABEL_CLASS)
+                .set("x", board_width / 2)
+                .set("y", board_height + LABEL_HEIGHT - 2)
+                .add(svg::node::Text::new(options.first_label.saturating_add(index).to_string()));
+            board = board.add(label);
+        }
 
-    let cell_size = 10;
+        document = document.add(board);
+    }
+
+    document.to_string()
+}
 
+/// Timing and ordering options for [`svg_animated_assembly`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationOptions {
+    /// Order in which the pieces appear on the board.
+    pub order: AssemblyOrder,
+    /// Delay between the appearance of two consecutive pieces, in milliseconds.
+    pub step_ms: u32,
+    /// Duration of the fade-in of each piece, in milliseconds.
+    pub fade_ms: u32,
+    /// How long the complete board stays visible before the animation restarts, in milliseconds.
+    pub hold_ms: u32,
+    /// Whether the animation loops forever or stops on the complete board.
+    pub repeat: bool,
+    /// Decorations drawn on every piece.
+    pub style: SvgOptions,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            order: AssemblyOrder::default(),
+            step_ms: 800,
+            fade_ms: 300,
+            hold_ms: 2000,
+            repeat: true,
+            style: SvgOptions::default(),
+        }
+    }
+}
+
+/// Converts a solution matrix to an SVG animation of its step-by-step assembly.
+///
+/// The board starts empty and pieces fade in one at a time, in `options.order`,
+/// using CSS keyframes so the result plays in any browser without scripting.
+/// Each piece is a `<g>` rendered like in [`svg_from_matrix`].
+///
+/// # Arguments
+///
+/// * `matrix` - A solution matrix, typically returned by [`crate::GameResolver`]
+/// * `options` - Ordering and timing of the animation
+///
+/// # Returns
+///
+/// An SVG document as a string
+#[must_use]
+pub fn svg_animated_assembly(matrix: &DMatrix<u32>, options: &AnimationOptions) -> String {
+    let width = matrix.ncols() * CELL_SIZE;
+    let height = matrix.nrows() * CELL_SIZE;
+    let steps = assembly_steps(matrix, options.order);
+
+    let mut style = format!(".{BOARD_CLASS}{{fill:#f0f0f0}}.{STEP_CLASS}{{opacity:0}}{}", border_css());
+    let cycle_ms = steps.len().saturating_sub(1) as u64 * u64::from(options.step_ms)
+        + 2 * u64::from(options.fade_ms)
+        + u64::from(options.hold_ms);
+    if options.repeat {
+        for index in 0..steps.len() {
+            // Fade in at its turn, stay until the end of the hold, then fade out with the others.
+            let start = index as u64 * u64::from(options.step_ms);
+            let shown = start + u64::from(options.fade_ms);
+            let hide = cycle_ms - u64::from(options.fade_ms);
+            // Writing to a String cannot fail.
+            let _ = write!(
+                style,
+                "@keyframes {STEP_CLASS}-{index}{{0%,{}%{{opacity:0}}{}%,{}%{{opacity:1}}100%{{opacity:0}}}}",
+                percent(start, cycle_ms),
+                percent(shown, cycle_ms),
+                percent(hide, cycle_ms),
+            );
+        }
+    } else {
+        let _ = write!(
+            style,
+            ".{STEP_CLASS}{{animation:{STEP_CLASS} {}ms ease-out forwards}}@keyframes {STEP_CLASS}{{from{{opacity:0}}to{{opacity:1}}}}",
+            options.fade_ms
+        );
+    }
+
+    let mut definitions = Definitions::new().add(Style::new(style));
+    if options.style.patterns {
+        definitions = add_pattern_definitions(definitions);
+    }
     let mut document = Document::new()
-        .set("viewBox", (0, 0, width * cell_size, height * cell_size));
+        .set("viewBox", (0, 0, width, height))
+        .add(definitions)
+        .add(Rectangle::new()
+            .set("class", BOARD_CLASS)
+            .set("width", width)
+            .set("height", height));
+
+    for (index, value) in steps.into_iter().enumerate() {
+        let animation = if options.repeat {
+            format!("animation:{STEP_CLASS}-{index} {cycle_ms}ms linear infinite")
+        } else {
+            format!("animation-delay:{}ms", index as u64 * u64::from(options.step_ms))
+        };
+        let step = add_board(Group::new(), matrix, BorderStyle::Shared, options.style, |cell| cell == value)
+            .set("class", STEP_CLASS)
+            .set("style", animation);
+        document = document.add(step);
+    }
+
+    document.to_string()
+}
+
+/// Positions of the elements of a challenge card, shared by both of its faces.
+struct CardLayout {
+    width: usize,
+    height: usize,
+    /// Top left corner of the board.
+    board: (usize, usize),
+    /// Top left corner of each piece silhouette, in game order.
+    pieces: Vec<(usize, usize)>,
+}
+
+impl CardLayout {
+    fn new(game: &Game) -> Self {
+        let columns = game.columns as usize;
+        let rows = game.rows() as usize;
+        let content_width = columns.max(CARD_MIN_COLUMNS) * CELL_SIZE;
+        let board = (CARD_MARGIN + (content_width - columns * CELL_SIZE) / 2, CARD_MARGIN);
+
+        // Flow the pieces left to right under the board dimensions, wrapping at the content width.
+        let top = CARD_MARGIN + rows * CELL_SIZE + CARD_TEXT_HEIGHT;
+        let (mut x, mut y, mut line_height) = (0, top, 0);
+        let mut pieces = Vec::with_capacity(game.pieces.len());
+        for piece in &game.pieces {
+            let width = piece.matrix.ncols() * CELL_SIZE;
+            if x > 0 && x + width > content_width {
+                x = 0;
+                y += line_height + CARD_PIECE_GAP;
+                line_height = 0;
+            }
+            pieces.push((CARD_MARGIN + x, y));
+            x += width + CARD_PIECE_GAP;
+            line_height = line_height.max(piece.matrix.nrows() * CELL_SIZE);
+        }
+
+        Self {
+            width: content_width + 2 * CARD_MARGIN,
+            height: y + line_height + CARD_MARGIN,
+            board,
+            pieces,
+        }
+    }
 
-    for y in 0..height {
-        for x in 0..width {
-            let color = matrix[(y, x)];
-            if color == 0 {
+    /// Creates a card face with its outline and the board dimensions.
+    fn face(&self, game: &Game) -> Document {
+        let style = format!(
+            ".{CARD_CLASS}{{fill:white;stroke:#333;stroke-width:1}}\
+             .{GRID_CLASS}{{fill:#f0f0f0;stroke:#999;stroke-width:0.5}}\
+             .{DIMENSIONS_CLASS}{{font-family:sans-serif;font-size:8px;text-anchor:middle;fill:#333}}{}",
+            border_css()
+        );
+        let dimensions = Text::new()
+            .set("class", DIMENSIONS_CLASS)
+            .set("x", self.width / 2)
+            .set("y", self.board.1 + game.rows() as usize * CELL_SIZE + CARD_TEXT_HEIGHT - 5)
+            .add(svg::node::Text::new(format!("{} × {}", game.columns, game.rows())));
+
+        Document::new()
+            .set("viewBox", (0, 0, self.width, self.height))
+            .add(Definitions::new().add(Style::new(style)))
+            .add(Rectangle::new()
+                .set("class", CARD_CLASS)
+                .set("x", 0.5)
+                .set("y", 0.5)
+                .set("width", self.width - 1)
+                .set("height", self.height - 1)
+                .set("rx", 4))
+            .add(dimensions)
+    }
+}
+
+/// Renders the front of a challenge card for an unsolved game.
+///
+/// The card shows the empty board grid with its dimensions (columns × rows)
+/// and, below it, the silhouette of every piece to place, in its color.
+/// Use [`svg_challenge_card_back`] to render the matching back of the card.
+///
+/// # Arguments
+///
+/// * `game` - The game to print; its board has [`Game::rows`] rows
+///
+/// # Returns
+///
+/// An SVG document as a string
+#[must_use]
+pub fn svg_challenge_card(game: &Game) -> String {
+    let layout = CardLayout::new(game);
+    let columns = game.columns as usize;
+    let rows = game.rows() as usize;
+
+    let mut grid = Group::new()
+        .set("class", GRID_CLASS)
+        .set("transform", format!("translate({},{})", layout.board.0, layout.board.1))
+        .add(Rectangle::new()
+            .set("width", columns * CELL_SIZE)
+            .set("height", rows * CELL_SIZE));
+    for column in 1..columns {
+        grid = grid.add(Line::new()
+            .set("x1", column * CELL_SIZE)
+            .set("y1", 0)
+            .set("x2", column * CELL_SIZE)
+            .set("y2", rows * CELL_SIZE));
+    }
+    for row in 1..rows {
+        grid = grid.add(Line::new()
+            .set("x1", 0)
+            .set("y1", row * CELL_SIZE)
+            .set("x2", columns * CELL_SIZE)
+            .set("y2", row * CELL_SIZE));
+    }
+
+    let mut document = layout.face(game).add(grid);
+    for (piece, (x, y)) in game.pieces.iter().zip(&layout.pieces) {
+        let silhouette = add_board(Group::new(), &(&piece.matrix * piece.color), BorderStyle::Shared, SvgOptions::default(), |_| true)
+            .set("transform", format!("translate({x},{y})"));
+        document = document.add(silhouette);
+    }
+
+    document.to_string()
+}
+
+/// Renders the back of a challenge card, showing a solution of the game.
+///
+/// The back has the same size as the front returned by [`svg_challenge_card`],
+/// with the solved board at the same position, so both faces can be printed duplex.
+///
+/// # Arguments
+///
+/// * `game` - The game printed on the front of the card
+/// * `solution` - A solution of `game`, typically returned by [`crate::GameResolver`]
+///
+/// # Returns
+///
+/// An SVG document as a string
+#[must_use]
+pub fn svg_challenge_card_back(game: &Game, solution: &DMatrix<u32>) -> String {
+    let layout = CardLayout::new(game);
+    let board = add_board(Group::new(), solution, BorderStyle::Shared, SvgOptions::default(), |_| true)
+        .set("transform", format!("translate({},{})", layout.board.0, layout.board.1));
+
+    layout.face(game).add(board).to_string()
+}
+
+/// CSS rule styling piece outlines drawn with [`BorderStyle::Shared`].
+fn border_css() -> String {
+    format!(".{BORDER_CLASS}{{stroke:{BORDER_COLOR};stroke-opacity:{BORDER_OPACITY};stroke-width:{BORDER_WIDTH}}}")
+}
+
+/// Formats `ms` as a keyframe percentage of `cycle_ms`.
+fn percent(ms: u64, cycle_ms: u64) -> String {
+    // Keyframe offsets do not need more precision than 1/100 of a percent.
+    let hundredths = (ms * 10_000).checked_div(cycle_ms).unwrap_or(0);
+    format!("{}.{:02}", hundredths / 100, hundredths % 100)
+}
+
+/// A piece of a board: the set of cells holding the same value.
+struct BoardPiece {
+    /// Cell value shared by all the cells of the piece (color plus encoded piece id).
+    value: u32,
+    /// Name of the piece, if recognized by [`PieceName::from_matrix`].
+    name: Option<PieceName>,
+    /// Cells of the piece, as (row, column), in reading order.
+    cells: Vec<(usize, usize)>,
+    /// Cell of the piece closest to its center, where its letter is printed.
+    label_cell: (usize, usize),
+}
+
+/// Splits a board into pieces, in the reading order of their first cell.
+fn board_pieces(matrix: &DMatrix<u32>) -> Vec<BoardPiece> {
+    let mut pieces: Vec<BoardPiece> = Vec::new();
+    for y in 0..matrix.nrows() {
+        for x in 0..matrix.ncols() {
+            let value = matrix[(y, x)];
+            if value == 0 {
                 continue;
             }
+            match pieces.iter_mut().find(|piece| piece.value == value) {
+                Some(piece) => piece.cells.push((y, x)),
+                None => pieces.push(BoardPiece { value, name: None, cells: vec![(y, x)], label_cell: (y, x) }),
+            }
+        }
+    }
+
+    for piece in &mut pieces {
+        let top = piece.cells.iter().map(|cell| cell.0).min().unwrap_or(0);
+        let bottom = piece.cells.iter().map(|cell| cell.0).max().unwrap_or(0);
+        let left = piece.cells.iter().map(|cell| cell.1).min().unwrap_or(0);
+        let right = piece.cells.iter().map(|cell| cell.1).max().unwrap_or(0);
 
-            let (r, g, b) = from_rgb_u32(color);
-            let svg_color = format!("rgb({r}, {g}, {b})");
+        let shape = DMatrix::from_fn(bottom - top + 1, right - left + 1, |y, x| {
+            u32::from(matrix[(top + y, left + x)] == piece.value)
+        });
+        piece.name = PieceName::from_matrix(&shape);
 
+        // Distances are compared in doubled units to stay on integers.
+        let center = (top + bottom, left + right);
+        if let Some(&cell) = piece.cells.iter()
+            .min_by_key(|&&(y, x)| (2 * y).abs_diff(center.0).pow(2) + (2 * x).abs_diff(center.1).pow(2)) {
+            piece.label_cell = cell;
+        }
+    }
+
+    pieces
+}
+
+/// Appends the SVG definition of every [`FillPattern`] to `definitions`.
+///
+/// Each pattern is referenced as `url(#gtp-pattern-<name>)`.
+fn add_pattern_definitions(mut definitions: Definitions) -> Definitions {
+    const PATTERNS: [FillPattern; 7] = [
+        FillPattern::DiagonalUp,
+        FillPattern::DiagonalDown,
+        FillPattern::Horizontal,
+        FillPattern::Vertical,
+        FillPattern::Cross,
+        FillPattern::Grid,
+        FillPattern::Dots,
+    ];
+    // Size of a pattern tile, in SVG user units: two stripes per cell.
+    const TILE: f32 = 5.0;
+    const HALF: f32 = TILE / 2.0;
+
+    for fill_pattern in PATTERNS {
+        let lines = match fill_pattern {
+            FillPattern::Solid | FillPattern::Dots => String::new(),
+            // Extra segments in the corners keep the diagonals continuous across tiles.
+            FillPattern::DiagonalUp => format!("M0,{TILE} L{TILE},0 M-1,1 L1,-1 M{a},{b} L{b},{a}", a = TILE - 1.0, b = TILE + 1.0),
+            FillPattern::DiagonalDown => format!("M0,0 L{TILE},{TILE} M-1,{a} L1,{b} M{a},-1 L{b},1", a = TILE - 1.0, b = TILE + 1.0),
+            FillPattern::Horizontal => format!("M0,{HALF} L{TILE},{HALF}"),
+            FillPattern::Vertical => format!("M{HALF},0 L{HALF},{TILE}"),
+            FillPattern::Cross => format!("M0,0 L{TILE},{TILE} M0,{TILE} L{TILE},0"),
+            FillPattern::Grid => format!("M0,{HALF} L{TILE},{HALF} M{HALF},0 L{HALF},{TILE}"),
+        };
+
+        let mut pattern = Pattern::new()
+            .set("id", format!("gtp-pattern-{}", fill_pattern.name()))
+            .set("patternUnits", "userSpaceOnUse")
+            .set("width", TILE)
+            .set("height", TILE);
+        pattern = if fill_pattern == FillPattern::Dots {
+            pattern.add(Circle::new()
+                .set("cx", HALF)
+                .set("cy", HALF)
+                .set("r", 1)
+                .set("fill", BORDER_COLOR)
+                .set("fill-opacity", 0.45))
+        } else {
+            pattern.add(Path::new()
+                .set("d", lines)
+                .set("stroke", BORDER_COLOR)
+                .set("stroke-opacity", 0.45)
+                .set("stroke-width", 0.8))
+        };
+        definitions = definitions.add(pattern);
+    }
+
+    definitions
+}
+
+/// Appends the pieces accepted by `include`, with their outlines and decorations, to `parent`.
+///
+/// Each piece is a `<g class="gtp-piece">` described in [`svg_from_matrix`].
+fn add_board<N: Node>(
+    mut parent: N,
+    matrix: &DMatrix<u32>,
+    border: BorderStyle,
+    options: SvgOptions,
+    include: impl Fn(u32) -> bool,
+) -> N {
+    let width = matrix.ncols();
+    let height = matrix.nrows();
+
+    for piece in board_pieces(matrix).into_iter().filter(|piece| include(piece.value)) {
+        let color = piece.value;
+        let (r, g, b) = from_rgb_u32(color);
+        let svg_color = format!("rgb({r}, {g}, {b})");
+
+        let mut group = Group::new().set("class", PIECE_CLASS);
+        let index = piece_index(color);
+        if let Some(index) = index {
+            group = group.set("data-piece-id", index);
+        }
+        if let Some(name) = piece.name {
+            group = group.set("data-piece-name", format!("{name:?}"));
+        }
+        let title = match (piece.name, index) {
+            (Some(name), _) => format!("{}, {} cells", name.description(), piece.cells.len()),
+            (None, Some(index)) => format!("Piece {}, {} cells", index + 1, piece.cells.len()),
+            (None, None) => format!("Piece, {} cells", piece.cells.len()),
+        };
+        group = group.add(Title::new().add(svg::node::Text::new(title)));
+
+        for &(y, x) in &piece.cells {
             // Draw the filled cell without stroke
             let rect = Rectangle::new()
-                .set("x", x * cell_size)
-                .set("y", y * cell_size)
-                .set("width", cell_size)
-                .set("height", cell_size)
-                .set("fill", svg_color)
+                .set("x", x * CELL_SIZE)
+                .set("y", y * CELL_SIZE)
+                .set("width", CELL_SIZE)
+                .set("height", CELL_SIZE)
+                .set("fill", svg_color.clone())
                 .set("stroke", "none"); // No stroke on the rect itself
 
-            document = document.add(rect);
+            group = group.add(rect);
+
+            if options.patterns {
+                let pattern = piece.name.map_or(FillPattern::Solid, FillPattern::for_piece);
+                if pattern != FillPattern::Solid {
+                    group = group.add(Rectangle::new()
+                        .set("x", x * CELL_SIZE)
+                        .set("y", y * CELL_SIZE)
+                        .set("width", CELL_SIZE)
+                        .set("height", CELL_SIZE)
+                        .set("fill", format!("url(#gtp-pattern-{})", pattern.name())));
+                }
+            }
 
             // Draw borders only if the neighbor is different
-            let border_color = "black";
-            let border_opacity = 0.3;
-            let border_width = 0.5;
+            let (left, top) = (x * CELL_SIZE, y * CELL_SIZE);
+            let (right, bottom) = ((x + 1) * CELL_SIZE, (y + 1) * CELL_SIZE);
 
             // Top
             if y == 0 || matrix[(y - 1, x)] != color {
-                let line = Line::new()
-                    .set("x1", x * cell_size)
-                    .set("y1", y * cell_size)
-                    .set("x2", (x + 1) * cell_size)
-                    .set("y2", y * cell_size)
-                    .set("stroke", border_color)
-                    .set("stroke-opacity", border_opacity)
-                    .set("stroke-width", border_width);
-                document = document.add(line);
+                group = group.add(border_line((left, top), (right, top), border));
             }
 
             // Bottom
             if y == height - 1 || matrix[(y + 1, x)] != color {
-                let line = Line::new()
-                    .set("x1", x * cell_size)
-                    .set("y1", (y + 1) * cell_size)
-                    .set("x2", (x + 1) * cell_size)
-                    .set("y2", (y + 1) * cell_size)
-                    .set("stroke", border_color)
-                    .set("stroke-opacity", border_opacity)
-                    .set("stroke-width", border_width);
-                document = document.add(line);
+                group = group.add(border_line((left, bottom), (right, bottom), border));
             }
 
             // Left
             if x == 0 || matrix[(y, x - 1)] != color {
-                let line = Line::new()
-                    .set("x1", x * cell_size)
-                    .set("y1", y * cell_size)
-                    .set("x2", x * cell_size)
-                    .set("y2", (y + 1) * cell_size)
-                    .set("stroke", border_color)
-                    .set("stroke-opacity", border_opacity)
-                    .set("stroke-width", border_width);
-                document = document.add(line);
+                group = group.add(border_line((left, top), (left, bottom), border));
             }
 
             // Right
             if x == width - 1 || matrix[(y, x + 1)] != color {
-                let line = Line::new()
-                    .set("x1", (x + 1) * cell_size)
-                    .set("y1", y * cell_size)
-                    .set("x2", (x + 1) * cell_size)
-                    .set("y2", (y + 1) * cell_size)
-                    .set("stroke", border_color)
-                    .set("stroke-opacity", border_opacity)
-                    .set("stroke-width", border_width);
-                document = document.add(line);
+                group = group.add(border_line((right, top), (right, bottom), border));
             }
         }
+
+        if let (true, Some(name)) = (options.letters, piece.name) {
+            let (row, col) = piece.label_cell;
+            // Dark letter with a light halo, readable on any piece color.
+            let letter = Text::new()
+                .set("x", col * CELL_SIZE + CELL_SIZE / 2)
+                .set("y", row * CELL_SIZE + CELL_SIZE / 2 + 2)
+                .set("font-family", "sans-serif")
+                .set("font-size", 6)
+                .set("font-weight", "bold")
+                .set("text-anchor", "middle")
+                .set("fill", BORDER_COLOR)
+                .set("stroke", "white")
+                .set("stroke-width", 1.2)
+                .set("paint-order", "stroke")
+                .add(svg::node::Text::new(name.letter().to_string()));
+            group = group.add(letter);
+        }
+
+        parent.append(group);
     }
 
-    document.to_string()
+    parent
+}
+
+/// Creates one segment of a piece outline.
+fn border_line(from: (usize, usize), to: (usize, usize), border: BorderStyle) -> Line {
+    let line = Line::new()
+        .set("x1", from.0)
+        .set("y1", from.1)
+        .set("x2", to.0)
+        .set("y2", to.1);
+
+    match border {
+        BorderStyle::Inline => line
+            .set("stroke", BORDER_COLOR)
+            .set("stroke-opacity", BORDER_OPACITY)
+            .set("stroke-width", BORDER_WIDTH),
+        BorderStyle::Shared => line.set("class", BORDER_CLASS),
+    }
 }
 
 /// Converts a 24-bit RGB hex color to separate R, G, B components.
@@ -123,4 +707,153 @@ pub const fn from_rgb_u32(c: u32) -> (u8, u8, u8) {
     let g = ((c & 0x0000_FF00u32) >> 8) as u8;
     let b = (c & 0x0000_00FFu32) as u8;
     (r, g, b)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Piece;
+
+    fn sample_board() -> DMatrix<u32> {
+        DMatrix::from_row_slice(2, 2, &[0xFF_0000, 0xFF_0000, 0x00_FF00, 0x00_FF00])
+    }
+
+    #[test]
+    fn test_svg_from_matrix() {
+        let svg = svg_from_matrix(&sample_board());
+        assert!(svg.contains(r#"viewBox="0 0 20 20""#));
+        assert_eq!(svg.matches("<rect").count(), 4);
+        assert!(svg.contains(r#"stroke-opacity="0.3""#));
+    }
+
+    #[test]
+    fn test_svg_from_matrix_with_options() {
+        // A tan bar of 2 cells above a brown L of 3 cells and a red square.
+        let board = DMatrix::from_row_slice(2, 3, &[
+            0x01F1_955A, 0x01F1_955A, 0x0257_1C11,
+            0x03DA_0022, 0x0257_1C11, 0x0257_1C11,
+        ]);
+
+        let plain = svg_from_matrix_with_options(&board, &SvgOptions::default());
+        assert_eq!(plain, svg_from_matrix(&board));
+        assert!(!plain.contains("<pattern"));
+
+        let svg = svg_from_matrix_with_options(&board, &SvgOptions { patterns: true, letters: true });
+        assert_eq!(svg.matches("<pattern").count(), 7);
+        // Tan bar is dotted, brown L is hatched, red square is solid.
+        assert_eq!(svg.matches("url(#gtp-pattern-dots)").count(), 2);
+        assert_eq!(svg.matches("url(#gtp-pattern-diagonal-up)").count(), 3);
+        assert_eq!(svg.matches("url(#gtp-pattern-").count(), 5);
+        assert_eq!(svg.matches("<text").count(), 3);
+        assert!(svg.contains("\nA\n") && svg.contains("\nB\n") && svg.contains("\nC\n"));
+    }
+
+    #[test]
+    fn test_svg_from_matrix_piece_groups() {
+        // Piece 2 of the game is a tan bar, piece 0 a brown L and piece 5 a red square.
+        let board = DMatrix::from_row_slice(2, 3, &[
+            0x03F1_955A, 0x03F1_955A, 0x0157_1C11,
+            0x0600_0000, 0x0157_1C11, 0x0157_1C11,
+        ]);
+        let svg = svg_from_matrix(&board);
+
+        assert_eq!(svg.matches(r#"<g class="gtp-piece""#).count(), 3);
+        assert!(svg.contains(r#"<g class="gtp-piece" data-piece-id="2" data-piece-name="TanBar2">"#));
+        assert!(svg.contains("<title>\nTan bar, 2 cells\n</title>"));
+        assert!(svg.contains(r#"<g class="gtp-piece" data-piece-id="0" data-piece-name="BrownL3">"#));
+        assert!(svg.contains(r#"<g class="gtp-piece" data-piece-id="5" data-piece-name="RedSquare1">"#));
+
+        let unknown = svg_from_matrix(&DMatrix::from_row_slice(1, 6, &[0x0600_0000; 6]));
+        assert!(unknown.contains(r#"<g class="gtp-piece" data-piece-id="5">"#));
+        assert!(unknown.contains("<title>\nPiece 6, 6 cells\n</title>"));
+    }
+
+    #[test]
+    fn test_svg_contact_sheet_layout() {
+        let boards = vec![sample_board(); 3];
+        let options = ContactSheetOptions { columns: 2, gap: 5, labels: false, ..ContactSheetOptions::default() };
+        let svg = svg_contact_sheet(&boards, &options);
+
+        // 2 boards of 20 units per row with one gap, 2 rows.
+        assert!(svg.contains(r#"viewBox="0 0 45 45""#));
+        assert!(svg.contains(r#"transform="translate(25,0)""#));
+        assert!(svg.contains(r#"transform="translate(0,25)""#));
+        assert_eq!(svg.matches("<rect").count(), 12);
+        // Outline styles are shared, not repeated on each line.
+        assert_eq!(svg.matches("stroke-opacity").count(), 1);
+        assert!(!svg.contains("<text"));
+    }
+
+    #[test]
+    fn test_svg_contact_sheet_labels() {
+        let boards = vec![sample_board(); 2];
+        let options = ContactSheetOptions { first_label: 11, ..ContactSheetOptions::default() };
+        let svg = svg_contact_sheet(&boards, &options);
+
+        assert_eq!(svg.matches("<text").count(), 2);
+        assert!(svg.contains("\n11\n"));
+        assert!(svg.contains("\n12\n"));
+    }
+
+    #[test]
+    fn test_svg_animated_assembly() {
+        let svg = svg_animated_assembly(&sample_board(), &AnimationOptions::default());
+
+        assert_eq!(svg.matches(r#"class="gtp-step""#).count(), 2);
+        assert!(svg.contains("@keyframes gtp-step-0{"));
+        assert!(svg.contains("@keyframes gtp-step-1{"));
+        // 1 step (800) + 2 fades (300) + hold (2000)
+        assert!(svg.contains("gtp-step-1 3400ms linear infinite"));
+        assert_eq!(svg.matches("<rect").count(), 5);
+    }
+
+    #[test]
+    fn test_svg_animated_assembly_once() {
+        let options = AnimationOptions { repeat: false, step_ms: 500, ..AnimationOptions::default() };
+        let svg = svg_animated_assembly(&sample_board(), &options);
+
+        assert!(!svg.contains("infinite"));
+        assert!(svg.contains("animation-delay:0ms"));
+        assert!(svg.contains("animation-delay:500ms"));
+    }
+
+    fn sample_game() -> Game {
+        let piece = Piece {
+            matrix: DMatrix::from_row_slice(1, 2, &[1, 1]),
+            color: 0xFF_0000,
+            tui_color: 0xFF_0000,
+        };
+        Game { columns: 2, pieces: vec![piece.clone(), piece] }
+    }
+
+    #[test]
+    fn test_svg_challenge_card() {
+        let svg = svg_challenge_card(&sample_game());
+
+        // Content is at least 8 cells wide, plus margins.
+        assert!(svg.contains(r#"viewBox="0 0 100 66""#));
+        assert!(svg.contains("2 × 2"));
+        // Card outline, empty board and 2 cells for each of the 2 pieces.
+        assert_eq!(svg.matches("<rect").count(), 6);
+        assert!(svg.contains(r#"transform="translate(35,46)""#));
+    }
+
+    #[test]
+    fn test_svg_challenge_card_back() {
+        let game = sample_game();
+        let front = svg_challenge_card(&game);
+        let back = svg_challenge_card_back(&game, &sample_board());
+
+        let view_box = |svg: &str| svg.lines().next().map(str::to_owned);
+        assert_eq!(view_box(&front), view_box(&back));
+        assert!(back.contains("2 × 2"));
+        // Card outline and the 4 cells of the solution.
+        assert_eq!(back.matches("<rect").count(), 5);
+    }
+
+    #[test]
+    fn test_svg_contact_sheet_empty() {
+        let svg = svg_contact_sheet(&[], &ContactSheetOptions::default());
+        assert!(svg.contains(r#"viewBox="0 0 0 0""#));
+    }
+}
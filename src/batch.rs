@@ -0,0 +1,235 @@
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use gtp_lib::game_resolver::piece_index;
+use gtp_lib::{Game, GameResolver, GameResolverTrait, Piece, PieceName};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cli::parse_piece;
+use crate::display::rows;
+
+/// Number of lines handed to each worker thread at once, when running in parallel.
+const LINES_PER_JOB: usize = 16;
+
+/// A game given as a JSON Lines record.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchGame {
+    /// Copied as is to the result record, to match results with their games.
+    #[serde(default)]
+    id: Option<Value>,
+    columns: u32,
+    pieces: Vec<PieceRef>,
+}
+
+/// A piece given by index in the full box or by name, as accepted by `--pieces`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum PieceRef {
+    Index(usize),
+    Name(String),
+}
+
+impl PieceRef {
+    pub fn piece(&self) -> Result<Piece, String> {
+        match self {
+            Self::Index(index) => parse_piece(&index.to_string()),
+            Self::Name(name) => parse_piece(name),
+        }
+    }
+}
+
+/// The result of one game.
+#[derive(Debug, Default, Serialize)]
+pub struct BatchRecord {
+    /// Line number of the game in the input, starting at 1.
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    columns: Option<u32>,
+    pieces: Vec<String>,
+    /// Number of solutions, `null` when the game could not be solved.
+    count: Option<usize>,
+    /// Time taken to solve the game, in milliseconds.
+    time_ms: f64,
+    /// Piece ids per cell of the first solution.
+    first_solution: Option<Vec<Vec<Option<usize>>>>,
+    error: Option<String>,
+}
+
+/// Parses a game line: either a JSON object, or the number of columns followed by comma-separated pieces.
+fn parse_game(line: &str) -> Result<(Option<Value>, Game), String> {
+    if line.starts_with('{') {
+        let game: BatchGame = serde_json::from_str(line).map_err(|error| format!("invalid JSON: {error}"))?;
+        let pieces = game.pieces.iter().map(PieceRef::piece).collect::<Result<Vec<Piece>, String>>()?;
+        return Ok((game.id, Game { columns: game.columns, pieces }));
+    }
+
+    let mut fields = line.split_whitespace();
+    let columns = fields.next().unwrap_or_default();
+    let columns = columns.parse().map_err(|_| format!("invalid number of columns '{columns}'"))?;
+    let pieces = fields.next().ok_or("missing piece list")?;
+    if let Some(extra) = fields.next() {
+        return Err(format!("unexpected '{extra}' after the piece list"));
+    }
+    let pieces = pieces.split(',').map(parse_piece).collect::<Result<Vec<Piece>, String>>()?;
+    Ok((None, Game { columns, pieces }))
+}
+
+/// Solves the game of one input line.
+pub fn process_line(line_number: usize, line: &str) -> BatchRecord {
+    let mut record = BatchRecord { line: line_number, ..BatchRecord::default() };
+    let game = match parse_game(line) {
+        Ok((id, game)) => {
+            record.id = id;
+            game
+        }
+        Err(error) => {
+            record.error = Some(error);
+            return record;
+        }
+    };
+
+    record.columns = Some(game.columns);
+    record.pieces = game
+        .pieces
+        .iter()
+        .map(|piece| PieceName::from_matrix(&piece.matrix).map_or_else(|| "?".to_string(), |name| format!("{name:?}")))
+        .collect();
+    if let Err(error) = game.validate() {
+        record.error = Some(format!("invalid game: {error}"));
+        return record;
+    }
+
+    let start = Instant::now();
+    let solutions = GameResolver.resolve(&game);
+    record.time_ms = start.elapsed().as_secs_f64() * 1000.0;
+    record.count = Some(solutions.len());
+    record.first_solution = solutions.first().map(|solution| rows(solution, piece_index));
+    record
+}
+
+/// Returns whether a line holds no game: blank lines and `#` comments are skipped.
+fn is_game(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+/// Solves the games of `input`, writing one JSON record per game to `output`, in input order.
+///
+/// Lines are read and records written as they go, so the input can be arbitrarily long.
+/// With more than one job, games are solved in parallel by chunks of lines.
+/// Returns the number of records with an error.
+pub fn run(input: impl BufRead, mut output: impl Write, jobs: usize) -> io::Result<usize> {
+    let chunk_size = if jobs > 1 { jobs * LINES_PER_JOB } else { 1 };
+    let mut errors = 0;
+    let mut chunk: Vec<(usize, String)> = Vec::with_capacity(chunk_size);
+    let mut lines = input.lines().enumerate();
+
+    loop {
+        chunk.clear();
+        for (index, line) in lines.by_ref() {
+            let line = line?;
+            if is_game(&line) {
+                chunk.push((index + 1, line));
+                if chunk.len() == chunk_size {
+                    break;
+                }
+            }
+        }
+        if chunk.is_empty() {
+            return Ok(errors);
+        }
+
+        for record in process_chunk(&chunk, jobs) {
+            errors += usize::from(record.error.is_some());
+            serde_json::to_writer(&mut output, &record)?;
+            writeln!(output)?;
+        }
+        output.flush()?;
+    }
+}
+
+/// Solves the games of a chunk on up to `jobs` threads, keeping the order of the lines.
+fn process_chunk(chunk: &[(usize, String)], jobs: usize) -> Vec<BatchRecord> {
+    if jobs <= 1 {
+        return chunk.iter().map(|(number, line)| process_line(*number, line)).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, BatchRecord)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.min(chunk.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((number, line)) = chunk.get(index) else { return done };
+                        done.push((index, process_line(*number, line)));
+                    }
+                })
+            })
+            .collect();
+        workers.into_iter().flat_map(|worker| worker.join().expect("Batch worker panicked")).collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, record)| record).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+# columns pieces
+5 RedSquare1,OrangeBar3,BrownL3,YellowZigZag4,BlueT4
+
+{\"id\": \"small\", \"columns\": 3, \"pieces\": [0, 2, 4]}
+4 PinkBar4,VioletSquare4
+5 RedSquare1
+two BlueT4
+{\"columns\": 5}
+";
+
+    fn records(jobs: usize) -> Vec<Value> {
+        let mut output = Vec::new();
+        let errors = run(INPUT.as_bytes(), &mut output, jobs).unwrap();
+        assert_eq!(errors, 3);
+        String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_run() {
+        let records = records(1);
+        assert_eq!(records.len(), 6);
+
+        assert_eq!(records[0]["line"], 2);
+        assert_eq!(records[0]["count"], 36);
+        assert_eq!(records[0]["first_solution"].as_array().unwrap().len(), 3);
+        assert_eq!(records[0]["error"], Value::Null);
+
+        assert_eq!(records[1]["id"], "small");
+        assert_eq!(records[1]["pieces"], serde_json::json!(["RedSquare1", "TanBar2", "BrownL3"]));
+        assert_eq!(records[1]["count"], 12);
+
+        assert_eq!(records[2]["count"], 0);
+        assert_eq!(records[2]["first_solution"], Value::Null);
+
+        assert_eq!(records[3]["error"], "invalid game: at least 2 pieces are needed, got 1");
+        assert_eq!(records[4]["error"], "invalid number of columns 'two'");
+        assert!(records[5]["error"].as_str().unwrap().starts_with("invalid JSON"));
+    }
+
+    #[test]
+    fn test_run_in_parallel_keeps_order() {
+        let strip_time = |mut record: Value| {
+            record["time_ms"] = Value::Null;
+            record
+        };
+        let sequential: Vec<_> = records(1).into_iter().map(strip_time).collect();
+        let parallel: Vec<_> = records(4).into_iter().map(strip_time).collect();
+        assert_eq!(parallel, sequential);
+    }
+}
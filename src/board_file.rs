@@ -0,0 +1,265 @@
+use std::fmt;
+
+use gtp_lib::game_resolver::with_piece_index;
+use gtp_lib::{Game, PieceName, SolutionError};
+use nalgebra::DMatrix;
+
+/// Character of the empty cells in a board file.
+pub const EMPTY_CELL: char = '.';
+
+/// A board transcribed as text: one line per row, one character per cell,
+/// the same character for all the cells of a piece.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    labels: DMatrix<char>,
+}
+
+/// The cells of a board sharing the same label.
+struct Region {
+    label: char,
+    /// Bounding box of the cells, 1 for the cells of the region.
+    matrix: DMatrix<u32>,
+    /// Board coordinates of the cells.
+    cells: Vec<(usize, usize)>,
+}
+
+/// Reasons why a board file cannot be read or is not a solution of a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    /// The file contains no cells.
+    Empty,
+    /// A row does not have the same number of cells as the first one.
+    RaggedRow { row: usize, cells: usize, expected: usize },
+    /// The board does not have the number of columns of the game.
+    ColumnMismatch { board: usize, game: u32 },
+    /// The cells of a label do not form a piece of the box.
+    UnknownShape { label: char },
+    /// The piece of a label is not part of the game, or is used more times than available.
+    NotInGame { label: char, name: PieceName },
+    /// The pieces of the board do not form a solution of the game.
+    NotASolution(SolutionError),
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the board is empty"),
+            Self::RaggedRow { row, cells, expected } => {
+                write!(f, "row {} has {cells} cells, expected {expected}", row + 1)
+            }
+            Self::ColumnMismatch { board, game } => {
+                write!(f, "the board has {board} columns, the game has {game}")
+            }
+            Self::UnknownShape { label } => write!(f, "piece '{label}' does not have the shape of a piece"),
+            Self::NotInGame { label, name } => {
+                write!(f, "piece '{label}' ({name:?}) is not part of the game or is used too many times")
+            }
+            Self::NotASolution(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+impl BoardError {
+    /// Returns whether the error comes from the format of the file rather than from its content.
+    pub const fn is_format_error(&self) -> bool {
+        matches!(self, Self::Empty | Self::RaggedRow { .. })
+    }
+}
+
+impl Board {
+    /// Parses a board file. Blank lines and trailing whitespace are ignored.
+    pub fn parse(text: &str) -> Result<Self, BoardError> {
+        let lines: Vec<Vec<char>> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.chars().collect())
+            .collect();
+
+        let expected = lines.first().ok_or(BoardError::Empty)?.len();
+        if let Some((row, line)) = lines.iter().enumerate().find(|(_, line)| line.len() != expected) {
+            return Err(BoardError::RaggedRow { row, cells: line.len(), expected });
+        }
+
+        Ok(Self { labels: DMatrix::from_fn(lines.len(), expected, |row, col| lines[row][col]) })
+    }
+
+    pub fn columns(&self) -> usize {
+        self.labels.ncols()
+    }
+
+    /// Returns the pieces of the board, in reading order of their first cell.
+    fn regions(&self) -> Vec<Region> {
+        let mut labels: Vec<char> = Vec::new();
+        let mut cells: Vec<Vec<(usize, usize)>> = Vec::new();
+        for row in 0..self.labels.nrows() {
+            for col in 0..self.labels.ncols() {
+                let label = self.labels[(row, col)];
+                if label == EMPTY_CELL {
+                    continue;
+                }
+                match labels.iter().position(|&known| known == label) {
+                    Some(index) => cells[index].push((row, col)),
+                    None => {
+                        labels.push(label);
+                        cells.push(vec![(row, col)]);
+                    }
+                }
+            }
+        }
+
+        labels
+            .into_iter()
+            .zip(cells)
+            .map(|(label, cells)| {
+                let top = cells.iter().map(|&(row, _)| row).min().unwrap_or(0);
+                let left = cells.iter().map(|&(_, col)| col).min().unwrap_or(0);
+                let bottom = cells.iter().map(|&(row, _)| row).max().unwrap_or(0);
+                let right = cells.iter().map(|&(_, col)| col).max().unwrap_or(0);
+                let mut matrix = DMatrix::zeros(bottom - top + 1, right - left + 1);
+                for &(row, col) in &cells {
+                    matrix[(row - top, col - left)] = 1;
+                }
+                Region { label, matrix, cells }
+            })
+            .collect()
+    }
+
+    /// Builds the game made of the pieces on the board, recognized by their shape.
+    ///
+    /// Fails when a piece is not part of the box, or appears more times than in the box.
+    pub fn recognized_game(&self) -> Result<Game, BoardError> {
+        let mut available = Game::game_with_all_pieces().pieces;
+        let mut pieces = Vec::new();
+        for region in self.regions() {
+            let name = PieceName::from_matrix(&region.matrix).ok_or(BoardError::UnknownShape { label: region.label })?;
+            let index = available
+                .iter()
+                .position(|piece| PieceName::from_matrix(&piece.matrix) == Some(name))
+                .ok_or(BoardError::NotInGame { label: region.label, name })?;
+            pieces.push(available.remove(index));
+        }
+        let columns = u32::try_from(self.columns()).expect("Column count too large");
+        Ok(Game { columns, pieces })
+    }
+
+    /// Matches the pieces of the board with the pieces of `game`.
+    ///
+    /// Returns the board as a solution matrix, each cell holding the color and the index
+    /// of its piece in the game, like the matrices returned by the solver.
+    /// When `complete` is set, the board must be a full solution, as checked by [`Game::verify`].
+    pub fn solution(&self, game: &Game, complete: bool) -> Result<DMatrix<u32>, BoardError> {
+        if u32::try_from(self.columns()).ok() != Some(game.columns) {
+            return Err(BoardError::ColumnMismatch { board: self.columns(), game: game.columns });
+        }
+
+        let names: Vec<Option<PieceName>> = game.pieces.iter().map(|piece| PieceName::from_matrix(&piece.matrix)).collect();
+        let mut used = vec![false; game.pieces.len()];
+        let mut solution = DMatrix::zeros(self.labels.nrows(), self.labels.ncols());
+        for region in self.regions() {
+            let name = PieceName::from_matrix(&region.matrix).ok_or(BoardError::UnknownShape { label: region.label })?;
+            let index = (0..game.pieces.len())
+                .find(|&index| !used[index] && names[index] == Some(name))
+                .ok_or(BoardError::NotInGame { label: region.label, name })?;
+            used[index] = true;
+            let value = with_piece_index(game.pieces[index].color, index);
+            for &cell in &region.cells {
+                solution[cell] = value;
+            }
+        }
+
+        if complete {
+            game.verify(&solution).map_err(BoardError::NotASolution)?;
+        }
+        Ok(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::ascii_solutions;
+    use gtp_lib::game_resolver::{piece_index, GameResolver, GameResolverTrait};
+
+    // Red square, orange bar, brown L, yellow zigzag and blue T on 5 columns
+    const SOLUTION: &str = "DDEEE\nADDEC\nBBBCC\n";
+
+    fn game() -> Game {
+        Game {
+            columns: 5,
+            pieces: vec![
+                PieceName::RedSquare1.piece(),
+                PieceName::OrangeBar3.piece(),
+                PieceName::BrownL3.piece(),
+                PieceName::YellowZigZag4.piece(),
+                PieceName::BlueT4.piece(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Board::parse("\n  \n"), Err(BoardError::Empty));
+        assert_eq!(Board::parse("AB\nABC\n"), Err(BoardError::RaggedRow { row: 1, cells: 3, expected: 2 }));
+        assert_eq!(Board::parse("\nAB  \nAB\n\n").map(|board| board.columns()), Ok(2));
+    }
+
+    #[test]
+    fn test_solution() {
+        let board = Board::parse(SOLUTION).unwrap();
+        let solution = board.solution(&game(), true).unwrap();
+        assert_eq!(piece_index(solution[(0, 0)]), Some(3));
+        assert_eq!(piece_index(solution[(1, 0)]), Some(0));
+        assert_eq!(piece_index(solution[(2, 4)]), Some(2));
+    }
+
+    #[test]
+    fn test_solution_errors() {
+        let incomplete = Board::parse("DDEEE\n.DDEC\nBBBCC\n").unwrap();
+        assert_eq!(
+            incomplete.solution(&game(), true),
+            Err(BoardError::NotASolution(SolutionError::EmptyCell { row: 1, col: 0 }))
+        );
+        assert_eq!(
+            incomplete.solution(&game(), false).map(|solution| solution[(1, 0)]),
+            Ok(0)
+        );
+
+        let wrong_piece = Board::parse("DDEEE\nADDEC\nBBCCC\n").unwrap();
+        assert_eq!(
+            wrong_piece.solution(&game(), true),
+            Err(BoardError::NotInGame { label: 'C', name: PieceName::GreenL4 })
+        );
+
+        let narrow = Board::parse("AB\n").unwrap();
+        assert_eq!(narrow.solution(&game(), true), Err(BoardError::ColumnMismatch { board: 2, game: 5 }));
+
+        let split_piece = Board::parse("DDEEE\nADDEA\n").unwrap();
+        assert_eq!(split_piece.solution(&game(), false), Err(BoardError::UnknownShape { label: 'A' }));
+    }
+
+    #[test]
+    fn test_ascii_output_round_trip() {
+        let game = game();
+        let solutions = GameResolver.resolve_page(&game, 0, 1);
+        let board = Board::parse(&ascii_solutions(&game, &solutions, false)).unwrap();
+        assert_eq!(board.solution(&game, true), Ok(solutions[0].clone()));
+    }
+
+    #[test]
+    fn test_recognized_game() {
+        let board = Board::parse(SOLUTION).unwrap();
+        let game = board.recognized_game().unwrap();
+        assert_eq!(game.columns, 5);
+        assert_eq!(game.pieces.len(), 5);
+        assert!(board.solution(&game, true).is_ok());
+
+        let three_squares = Board::parse("ABC\n").unwrap();
+        assert_eq!(
+            three_squares.recognized_game().map(|game| game.pieces.len()),
+            Err(BoardError::NotInGame { label: 'C', name: PieceName::RedSquare1 })
+        );
+    }
+}
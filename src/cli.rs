@@ -0,0 +1,354 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use gtp_lib::assembly::AssemblyOrder;
+use gtp_lib::palette::Palette;
+use gtp_lib::svg_renderer::SvgOptions;
+use gtp_lib::{Game, Piece, PieceName};
+
+/// Pieces solved when `--pieces` is not given.
+const DEFAULT_PIECES: [PieceName; 5] = [
+    PieceName::RedSquare1,
+    PieceName::OrangeBar3,
+    PieceName::BrownL3,
+    PieceName::YellowZigZag4,
+    PieceName::BlueT4,
+];
+
+/// Number of columns when `--columns` is not given.
+const DEFAULT_COLUMNS: u32 = 5;
+
+/// Solver for the "Gagne Ton Papa!" puzzle game.
+///
+/// Without a subcommand, solves the game like `solve`.
+///
+/// Exit status: 0 on success, 1 when the game has no solution or the board is not a solution,
+/// 2 when the game, the board file or the arguments are invalid.
+#[derive(Debug, Parser)]
+#[command(version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub solve: SolveArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the solutions of a game
+    Solve(SolveArgs),
+    /// Print the number of solutions of a game
+    Count(GameArgs),
+    /// Check that a board file is a solution of a game
+    Verify(VerifyArgs),
+    /// Render a board file as SVG, PNG or colored blocks
+    Render(RenderArgs),
+    /// List the pieces of a game, or of the full box when no pieces are given
+    Catalog(CatalogArgs),
+    /// Pick pieces from the box and browse the solutions in an interactive terminal UI
+    Tui(GameArgs),
+    /// Play the game in the terminal: place the pieces yourself, with undo and hints
+    Play(GameArgs),
+    /// Solve many games from a file, printing one JSON record per game
+    Batch(BatchArgs),
+    /// Answer JSON-RPC 2.0 requests read from the standard input, one per line
+    Rpc,
+    /// Serve the solver as an HTTP JSON API, on localhost by default
+    #[cfg(feature = "server")]
+    Serve(ServeArgs),
+}
+
+/// Flags selecting the game, shared by all subcommands.
+#[derive(Debug, Clone, Args)]
+pub struct GameArgs {
+    /// Comma-separated pieces to place, by name (`BlueT4`, `blue-t4`)
+    /// or by index in the full box of 18 pieces (0 to 17)
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_piece)]
+    pub pieces: Vec<Piece>,
+
+    /// Number of columns of the board [default: 5, or the width of the board file]
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub columns: Option<u32>,
+
+    /// Color palette: classic, terminal, okabe-ito, tol-bright or high-contrast
+    /// [default: terminal for colored blocks, classic otherwise]
+    #[arg(long, value_parser = parse_palette)]
+    pub palette: Option<Palette>,
+}
+
+/// SVG decorations, shared by the subcommands printing SVG or PNG.
+#[derive(Debug, Clone, Copy, Args)]
+pub struct StyleArgs {
+    /// Draw a hatch or dot pattern over each piece in SVG and PNG output
+    #[arg(long)]
+    pub patterns: bool,
+
+    /// Print a letter on each piece in SVG and PNG output
+    #[arg(long)]
+    pub letters: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SolveArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+
+    /// Maximum number of solutions to output
+    #[arg(short = 'n', long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub limit: Option<usize>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Terminal)]
+    pub format: OutputFormat,
+
+    /// Do not print the list of pieces before the solutions
+    #[arg(long)]
+    pub no_piece_list: bool,
+
+    /// Number of boards per row in `svg` output
+    #[arg(long, default_value_t = 4)]
+    pub sheet_columns: usize,
+
+    /// Order in which pieces are assembled in `animated-svg` and `frames` output
+    #[arg(long, value_enum, default_value_t = Order::Solver)]
+    pub order: Order,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
+
+    /// Write PNG files to this directory instead of printing SVG
+    #[cfg(feature = "png")]
+    #[arg(long, value_name = "DIR")]
+    pub png: Option<PathBuf>,
+
+    /// Write each solution to `DIR/<game-code>/<index>.<format>`, with an `index.json` file,
+    /// instead of printing them
+    #[arg(long, value_name = "DIR")]
+    pub export: Option<PathBuf>,
+
+    /// Format of the files written by `--export`
+    #[arg(long, value_enum, default_value_t = ExportFormat::Svg)]
+    pub export_format: ExportFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+
+    /// Board file, `-` for the standard input.
+    ///
+    /// One line per row and one character per cell, the same character for all the cells of a piece,
+    /// as printed by `solve --format ascii --no-piece-list --limit 1`. Without `--pieces`, pieces are recognized by their shape.
+    pub board: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct RenderArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+
+    /// Board file, `-` for the standard input, in the format read by `verify`.
+    /// `.` marks an empty cell.
+    pub board: PathBuf,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = RenderFormat::Svg)]
+    pub format: RenderFormat,
+
+    /// Output file [default: standard output]
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Scale factor of PNG output
+    #[arg(long, default_value_t = 10.0)]
+    pub scale: f32,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct CatalogArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+
+    /// Print the pieces as JSON instead of tab-separated lines
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct BatchArgs {
+    /// File of games, `-` for the standard input.
+    ///
+    /// One game per line: the number of columns followed by comma-separated pieces (`5 0,5,4,9,8`),
+    /// or a JSON object (`{"id": "a", "columns": 5, "pieces": ["BlueT4", 3]}`).
+    /// Blank lines and lines starting with `#` are skipped.
+    #[arg(default_value = "-")]
+    pub input: PathBuf,
+
+    /// Output file [default: standard output]
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Number of games solved in parallel
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: u16,
+}
+
+#[cfg(feature = "server")]
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Address to listen on; use 0.0.0.0 to accept other machines of the network
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    pub address: std::net::SocketAddr,
+
+    /// Maximum time spent solving for one request, in milliseconds
+    #[arg(long, default_value_t = 5000, value_parser = clap::value_parser!(u64).range(1..))]
+    pub budget_ms: u64,
+
+    /// Number of requests answered in parallel
+    #[arg(short, long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    pub threads: u16,
+}
+
+/// How solutions are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored blocks
+    Terminal,
+    /// One letter per piece
+    Ascii,
+    /// Piece ids per cell, as JSON
+    Json,
+    /// All solutions in a single SVG contact sheet
+    Svg,
+    /// Animated SVG assembly of the first solution
+    AnimatedSvg,
+    /// Assembly of the first solution, one piece at a time, as colored blocks
+    Frames,
+    /// Front of the challenge card of the game, as SVG
+    Card,
+    /// Back of the challenge card, showing the first solution, as SVG
+    CardBack,
+    /// Self-contained HTML page with the pieces, statistics and a contact sheet of all solutions
+    Html,
+}
+
+/// How a board file is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RenderFormat {
+    /// SVG document
+    Svg,
+    /// PNG image (requires the `png` feature)
+    Png,
+    /// Colored blocks, with ANSI escape codes
+    Ansi,
+}
+
+/// Format of the solution files written by `solve --export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// SVG document
+    Svg,
+    /// PNG image (requires the `png` feature)
+    Png,
+    /// Piece ids per cell, as JSON
+    Json,
+}
+
+/// Order in which the pieces of a solution are assembled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Order {
+    /// Order used by the solver
+    Solver,
+    /// Largest pieces first
+    Largest,
+    /// Row by row, from the top
+    Top,
+}
+
+impl From<Order> for AssemblyOrder {
+    fn from(order: Order) -> Self {
+        match order {
+            Order::Solver => Self::Solver,
+            Order::Largest => Self::LargestFirst,
+            Order::Top => Self::TopToBottom,
+        }
+    }
+}
+
+impl GameArgs {
+    /// Builds the game selected by `--pieces` and `--columns`, colored with `--palette` or `default_palette`.
+    pub fn game(&self, default_palette: Palette) -> Game {
+        let pieces = if self.pieces.is_empty() {
+            DEFAULT_PIECES.iter().map(PieceName::piece).collect()
+        } else {
+            self.pieces.clone()
+        };
+        self.colored(Game { columns: self.columns.unwrap_or(DEFAULT_COLUMNS), pieces }, default_palette)
+    }
+
+    /// Recolors `game` with `--palette` or `default_palette`.
+    pub fn colored(&self, mut game: Game, default_palette: Palette) -> Game {
+        self.palette.unwrap_or(default_palette).apply(&mut game);
+        game
+    }
+}
+
+impl SolveArgs {
+    /// Returns the palette used when `--palette` is not given.
+    pub const fn default_palette(&self) -> Palette {
+        // Use TUI colors (high contrast) for the terminal app
+        match self.format {
+            OutputFormat::Terminal | OutputFormat::Frames => Palette::Terminal,
+            _ => Palette::Classic,
+        }
+    }
+}
+
+impl From<StyleArgs> for SvgOptions {
+    fn from(style: StyleArgs) -> Self {
+        Self { patterns: style.patterns, letters: style.letters }
+    }
+}
+
+/// Parses a piece given by name or by index in [`Game::game_with_all_pieces`].
+pub fn parse_piece(arg: &str) -> Result<Piece, String> {
+    if let Ok(index) = arg.parse::<usize>() {
+        let all = Game::game_with_all_pieces();
+        return all.piece(index).cloned().ok_or_else(|| {
+            format!("piece index {index} is out of range, expected 0 to {}", all.pieces.len() - 1)
+        });
+    }
+    PieceName::from_name(arg)
+        .map(|name| name.piece())
+        .ok_or_else(|| {
+            let names: Vec<_> = PieceName::ALL.iter().map(|name| format!("{name:?}")).collect();
+            format!("unknown piece '{arg}', expected an index or one of {}", names.join(", "))
+        })
+}
+
+fn parse_palette(arg: &str) -> Result<Palette, String> {
+    Palette::from_name(arg).ok_or_else(|| {
+        let names: Vec<_> = Palette::ALL.iter().map(|palette| palette.name()).collect();
+        format!("unknown palette '{arg}', expected one of {}", names.join(", "))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_must_be_positive() {
+        // A limit of 0 would always report "no solution" and exit with 1
+        assert!(Cli::try_parse_from(["gtp", "solve", "--limit", "0"]).is_err());
+        assert!(Cli::try_parse_from(["gtp", "--limit", "0"]).is_err());
+        let cli = Cli::try_parse_from(["gtp", "solve", "--limit", "1"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Solve(SolveArgs { limit: Some(1), .. }))));
+    }
+}
@@ -0,0 +1,387 @@
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Instant;
+
+use gtp_lib::{Game, GameResolver, GameResolverTrait, PieceName};
+use gtp_lib::assembly::assembly_frames;
+use gtp_lib::dead_end;
+use gtp_lib::palette::Palette;
+use gtp_lib::svg_renderer::{
+    svg_animated_assembly, svg_challenge_card, svg_challenge_card_back, svg_contact_sheet,
+    svg_from_matrix_with_options, AnimationOptions, ContactSheetOptions, SvgOptions,
+};
+#[cfg(feature = "png")]
+use gtp_lib::png_renderer::png_from_svg;
+
+use nalgebra::DMatrix;
+use serde::Serialize;
+
+use crate::board_file::{Board, BoardError};
+use crate::cli::{BatchArgs, CatalogArgs, GameArgs, OutputFormat, RenderArgs, RenderFormat, SolveArgs, VerifyArgs};
+use crate::display;
+use crate::export;
+use crate::report::{html_report, SolverStats};
+
+/// Exit status on success.
+const EXIT_SUCCESS: u8 = 0;
+/// Exit status when the game has no solution, or the board is not a solution.
+const EXIT_FAILURE: u8 = 1;
+/// Exit status when the game or the input is invalid (clap also uses 2 for usage errors).
+const EXIT_INVALID: u8 = 2;
+
+/// Prints `message` as an error of the invalid input, and returns the matching exit status.
+fn invalid(message: impl std::fmt::Display) -> ExitCode {
+    eprintln!("error: {message}");
+    ExitCode::from(EXIT_INVALID)
+}
+
+/// Validates `game`, printing the error if any.
+fn validate(game: &Game) -> Result<(), ExitCode> {
+    game.validate().map_err(|error| invalid(format_args!("invalid game: {error}")))
+}
+
+fn solved(found: bool) -> ExitCode {
+    ExitCode::from(if found { EXIT_SUCCESS } else { EXIT_FAILURE })
+}
+
+pub fn solve(args: &SolveArgs) -> ExitCode {
+    let mut game = args.game.game(args.default_palette());
+    if let Err(code) = validate(&game) {
+        return code;
+    }
+
+    if let Some(dir) = &args.export {
+        game = export::canonical(game);
+        let solutions = find_solutions(args, &game);
+        return match export::export(dir, &game, &solutions, args.export_format, SvgOptions::from(args.style)) {
+            Ok(game_dir) => {
+                println!("{}", game_dir.join(export::INDEX_FILE).display());
+                solved(!solutions.is_empty())
+            }
+            Err(error) => invalid(format_args!("cannot export the solutions: {error}")),
+        };
+    }
+
+    let start = Instant::now();
+    let solutions = find_solutions(args, &game);
+    let duration = start.elapsed();
+
+    #[cfg(feature = "png")]
+    if let Some(dir) = &args.png {
+        return match write_png(args, &game, &solutions, dir) {
+            Ok(()) => solved(!solutions.is_empty()),
+            Err(error) => invalid(format_args!("cannot write PNG: {error}")),
+        };
+    }
+
+    let piece_list = !args.no_piece_list;
+    let style = SvgOptions::from(args.style);
+    match args.format {
+        OutputFormat::Terminal => {
+            if piece_list {
+                display::print_pieces(&game.pieces);
+            }
+            display::print_solutions(&game, &solutions);
+        }
+        OutputFormat::Ascii => display::print_ascii(&game, &solutions, piece_list),
+        OutputFormat::Json => display::print_json(&game, &solutions, piece_list),
+        OutputFormat::Svg => {
+            let options = ContactSheetOptions { columns: args.sheet_columns, style, ..ContactSheetOptions::default() };
+            println!("{}", svg_contact_sheet(&solutions, &options));
+        }
+        OutputFormat::AnimatedSvg => {
+            if let Some(solution) = solutions.first() {
+                let options = AnimationOptions { order: args.order.into(), style, ..AnimationOptions::default() };
+                println!("{}", svg_animated_assembly(solution, &options));
+            }
+        }
+        OutputFormat::Frames => {
+            if let Some(solution) = solutions.first() {
+                let frames = assembly_frames(solution, args.order.into());
+                for (index, frame) in frames.iter().enumerate() {
+                    println!("Step {}/{}", index + 1, frames.len());
+                    display::display(frame);
+                    println!("--------------------");
+                }
+            }
+        }
+        OutputFormat::Card => println!("{}", svg_challenge_card(&game)),
+        OutputFormat::CardBack => {
+            if let Some(solution) = solutions.first() {
+                println!("{}", svg_challenge_card_back(&game, solution));
+            }
+        }
+        OutputFormat::Html => {
+            let options = ContactSheetOptions { columns: args.sheet_columns, style, ..ContactSheetOptions::default() };
+            let stats = SolverStats { duration, limit: args.limit };
+            print!("{}", html_report(&game, &solutions, stats, &options));
+        }
+    }
+
+    if solutions.is_empty() {
+        eprintln!("no solution: {}", dead_end::explain_unsolvable(&game, 0, &game.piece_ids()));
+    }
+    solved(!solutions.is_empty())
+}
+
+/// Finds the solutions needed by the output format, up to `--limit`.
+fn find_solutions(args: &SolveArgs, game: &Game) -> Vec<DMatrix<u32>> {
+    let resolver = GameResolver;
+    let limit = match args.format {
+        OutputFormat::AnimatedSvg | OutputFormat::Frames | OutputFormat::Card | OutputFormat::CardBack => Some(1),
+        _ => args.limit,
+    };
+    match limit {
+        Some(limit) => resolver.resolve_page(game, 0, limit),
+        None => resolver.resolve(game),
+    }
+}
+
+/// Writes the output as PNG files in `dir`, printing their paths.
+#[cfg(feature = "png")]
+fn write_png(args: &SolveArgs, game: &Game, solutions: &[DMatrix<u32>], dir: &Path) -> std::io::Result<()> {
+    let style = SvgOptions::from(args.style);
+    let files: Vec<(String, String)> = match args.format {
+        OutputFormat::Terminal | OutputFormat::Ascii | OutputFormat::Json | OutputFormat::Svg | OutputFormat::Html => solutions
+            .iter()
+            .enumerate()
+            .map(|(index, solution)| {
+                (format!("solution-{:03}.png", index + 1), svg_from_matrix_with_options(solution, &style))
+            })
+            .collect(),
+        OutputFormat::AnimatedSvg | OutputFormat::Frames => solutions
+            .first()
+            .map(|solution| assembly_frames(solution, args.order.into()))
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                (format!("frame-{:02}.png", index + 1), svg_from_matrix_with_options(frame, &style))
+            })
+            .collect(),
+        OutputFormat::Card | OutputFormat::CardBack => {
+            let mut faces = vec![("card-front.png".to_string(), svg_challenge_card(game))];
+            if let Some(solution) = solutions.first() {
+                faces.push(("card-back.png".to_string(), svg_challenge_card_back(game, solution)));
+            }
+            faces
+        }
+    };
+
+    std::fs::create_dir_all(dir)?;
+    for (name, svg) in files {
+        let png = png_from_svg(&svg, 10.0).map_err(std::io::Error::other)?;
+        let path = dir.join(name);
+        std::fs::write(&path, png)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+pub fn count(args: &GameArgs) -> ExitCode {
+    let game = args.game(Palette::Classic);
+    if let Err(code) = validate(&game) {
+        return code;
+    }
+
+    let count = GameResolver.resolve_count(&game);
+    println!("{count}");
+    solved(count > 0)
+}
+
+/// Reads a board file, `-` being the standard input.
+fn read_board(path: &Path) -> Result<Board, ExitCode> {
+    let text = if path == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        std::fs::read_to_string(path)
+    };
+    let text = text.map_err(|error| invalid(format_args!("cannot read {}: {error}", path.display())))?;
+    Board::parse(&text).map_err(|error| invalid(format_args!("{}: {error}", path.display())))
+}
+
+/// Returns the game of the board: the one selected by `--pieces`, or the pieces recognized on the board.
+fn board_game(args: &GameArgs, board: &Board, default_palette: Palette) -> Result<Game, BoardError> {
+    let game = if args.pieces.is_empty() {
+        board.recognized_game()?
+    } else {
+        args.game(default_palette)
+    };
+    let columns = args.columns.unwrap_or_else(|| u32::try_from(board.columns()).expect("Column count too large"));
+    Ok(args.colored(Game { columns, ..game }, default_palette))
+}
+
+pub fn verify(args: &VerifyArgs) -> ExitCode {
+    let board = match read_board(&args.board) {
+        Ok(board) => board,
+        Err(code) => return code,
+    };
+
+    match board_game(&args.game, &board, Palette::Classic).and_then(|game| board.solution(&game, true)) {
+        Ok(_) => {
+            println!("valid");
+            ExitCode::from(EXIT_SUCCESS)
+        }
+        Err(error) if error.is_format_error() => invalid(error),
+        Err(error) => {
+            println!("invalid: {error}");
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+pub fn render(args: &RenderArgs) -> ExitCode {
+    let board = match read_board(&args.board) {
+        Ok(board) => board,
+        Err(code) => return code,
+    };
+
+    let default_palette = if args.format == RenderFormat::Ansi { Palette::Terminal } else { Palette::Classic };
+    let solution = match board_game(&args.game, &board, default_palette).and_then(|game| board.solution(&game, false)) {
+        Ok(solution) => solution,
+        Err(error) => return invalid(error),
+    };
+
+    let style = SvgOptions::from(args.style);
+    let output = match args.format {
+        RenderFormat::Svg => format!("{}\n", svg_from_matrix_with_options(&solution, &style)).into_bytes(),
+        #[cfg(feature = "png")]
+        RenderFormat::Png => match png_from_svg(&svg_from_matrix_with_options(&solution, &style), args.scale) {
+            Ok(png) => png,
+            Err(error) => return invalid(error),
+        },
+        #[cfg(not(feature = "png"))]
+        RenderFormat::Png => return invalid("PNG output requires the `png` feature"),
+        RenderFormat::Ansi => display::ansi(&solution).into_bytes(),
+    };
+
+    let written = match &args.output {
+        Some(path) => std::fs::write(path, output),
+        None => std::io::stdout().write_all(&output),
+    };
+    match written {
+        Ok(()) => ExitCode::from(EXIT_SUCCESS),
+        Err(error) => invalid(format_args!("cannot write the output: {error}")),
+    }
+}
+
+/// A piece, as printed by `catalog --json`.
+#[derive(Serialize)]
+pub struct CatalogPiece {
+    id: usize,
+    name: Option<String>,
+    description: Option<&'static str>,
+    cells: u32,
+    /// Color as a `#RRGGBB` hex string.
+    color: String,
+    /// Cells of the piece, 1 when occupied.
+    matrix: Vec<Vec<u32>>,
+}
+
+/// Describes the pieces of `game`, with their ids in the game.
+pub fn catalog_pieces(game: &Game) -> Vec<CatalogPiece> {
+    game.pieces
+        .iter()
+        .enumerate()
+        .map(|(id, piece)| {
+            let name = PieceName::from_matrix(&piece.matrix);
+            CatalogPiece {
+                id,
+                name: name.map(|name| format!("{name:?}")),
+                description: name.map(PieceName::description),
+                cells: piece.cells(),
+                color: format!("#{:06X}", piece.color),
+                matrix: piece.matrix.row_iter().map(|row| row.iter().copied().collect()).collect(),
+            }
+        })
+        .collect()
+}
+
+pub fn catalog(args: &CatalogArgs) -> ExitCode {
+    let game = if args.game.pieces.is_empty() {
+        args.game.colored(Game::game_with_all_pieces(), Palette::Classic)
+    } else {
+        args.game.game(Palette::Classic)
+    };
+
+    let pieces = catalog_pieces(&game);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&pieces).expect("Cannot serialize pieces"));
+    } else {
+        for piece in &pieces {
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                piece.id,
+                piece.name.as_deref().unwrap_or("-"),
+                piece.cells,
+                piece.color,
+                piece.description.unwrap_or("-")
+            );
+        }
+    }
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+pub fn tui(args: &GameArgs) -> ExitCode {
+    match crate::tui::run(args) {
+        Ok(()) => ExitCode::from(EXIT_SUCCESS),
+        Err(error) => invalid(format_args!("terminal error: {error}")),
+    }
+}
+
+pub fn play(args: &GameArgs) -> ExitCode {
+    let game = args.game(Palette::Terminal);
+    if let Err(code) = validate(&game) {
+        return code;
+    }
+
+    match crate::play::run(game) {
+        Ok(()) => ExitCode::from(EXIT_SUCCESS),
+        Err(error) => invalid(format_args!("terminal error: {error}")),
+    }
+}
+
+pub fn batch(args: &BatchArgs) -> ExitCode {
+    let input: Box<dyn BufRead> = if args.input == Path::new("-") {
+        Box::new(std::io::stdin().lock())
+    } else {
+        match std::fs::File::open(&args.input) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(error) => return invalid(format_args!("cannot read {}: {error}", args.input.display())),
+        }
+    };
+    let output: Box<dyn Write> = match &args.output {
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(error) => return invalid(format_args!("cannot write {}: {error}", path.display())),
+        },
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    match crate::batch::run(input, output, usize::from(args.jobs)) {
+        Ok(0) => ExitCode::from(EXIT_SUCCESS),
+        Ok(errors) => invalid(format_args!("{errors} games could not be solved")),
+        Err(error) => invalid(format_args!("batch failed: {error}")),
+    }
+}
+
+pub fn rpc() -> ExitCode {
+    match crate::rpc::run(std::io::stdin().lock(), std::io::stdout().lock()) {
+        Ok(()) => ExitCode::from(EXIT_SUCCESS),
+        Err(error) => invalid(format_args!("rpc failed: {error}")),
+    }
+}
+
+#[cfg(feature = "server")]
+pub fn serve(args: &crate::cli::ServeArgs) -> ExitCode {
+    let server = match tiny_http::Server::http(args.address) {
+        Ok(server) => server,
+        Err(error) => return invalid(format_args!("cannot listen on {}: {error}", args.address)),
+    };
+    eprintln!("Listening on http://{}", args.address);
+    let options = crate::server::ServerOptions { budget: std::time::Duration::from_millis(args.budget_ms) };
+    crate::server::serve(&server, usize::from(args.threads), options);
+    ExitCode::from(EXIT_SUCCESS)
+}
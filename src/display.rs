@@ -0,0 +1,196 @@
+use gtp_lib::game_resolver::piece_index;
+use gtp_lib::{Game, Piece, PieceName};
+
+use colored::*;
+use nalgebra::DMatrix;
+use serde::Serialize;
+
+/// Letters used by the ASCII output, indexed by piece id.
+const ASCII_LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// Character of the empty cells in the ASCII output.
+const ASCII_EMPTY: char = '.';
+
+/// Height of a cell in colored blocks output, in lines (a cell is twice as wide, in characters).
+pub const DISPLAY_SIZE: usize = 2;
+/// Character drawn in the colored blocks output.
+pub const BLOCK_CHAR: &str = "█";
+/// Color of the empty cells in the colored blocks output.
+pub const DEFAULT_COLOR: u32 = 0x0F0F0F;
+
+pub fn print_piece(piece: &Piece) {
+    let matrix = &piece.matrix * piece.tui_color;
+    display(&matrix);
+}
+
+pub fn print_pieces(pieces: &[Piece]) {
+    for piece in pieces {
+        print_piece(piece);
+        println!();
+    }
+}
+
+pub fn print_solutions(game: &Game, solutions: &[DMatrix<u32>]) {
+    for solution in solutions {
+        display(solution);
+        println!("--------------------");
+    }
+
+    println!(
+        "{}x{}: {} -> {} solutions",
+        game.rows(),
+        game.columns,
+        game.is_valid(),
+        solutions.len()
+    );
+}
+
+pub fn display(matrix: &DMatrix<u32>) {
+    print!("{}", ansi(matrix));
+}
+
+/// Renders a matrix as colored blocks, with ANSI escape codes.
+pub fn ansi(matrix: &DMatrix<u32>) -> String {
+    let block = BLOCK_CHAR.repeat(DISPLAY_SIZE * 2);
+    let mut text = String::new();
+
+    for row in matrix.row_iter() {
+        for _i in 0..DISPLAY_SIZE {
+            for &color in row.iter() {
+                let (r, g, b) = if color > 0 {
+                    from_rgb_u32(color)
+                } else {
+                    from_rgb_u32(DEFAULT_COLOR)
+                };
+
+                text.push_str(&block.truecolor(r, g, b).to_string());
+            }
+            text.push('\n');
+        }
+    }
+    text
+}
+
+pub fn from_rgb_u32(c: u32) -> (u8, u8, u8) {
+    let r = ((c & 0x00FF_0000u32) >> 16) as u8;
+    let g = ((c & 0x0000_FF00u32) >> 8) as u8;
+    let b = (c & 0x0000_00FFu32) as u8;
+    (r, g, b)
+}
+
+/// Returns the letter of a piece id in the ASCII output.
+fn ascii_letter(id: usize) -> char {
+    ASCII_LETTERS.get(id).map_or('?', |&letter| char::from(letter))
+}
+
+/// Renders a matrix with one letter per piece, `A` being the first piece of the game.
+///
+/// Piece matrices (cells set to 1) are rendered with `#`.
+pub fn ascii(matrix: &DMatrix<u32>) -> String {
+    let mut text = String::new();
+    for row in matrix.row_iter() {
+        for &cell in row.iter() {
+            text.push(match cell {
+                0 => ASCII_EMPTY,
+                1 => '#',
+                _ => piece_index(cell).map_or('?', ascii_letter),
+            });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Returns the pieces when `piece_list` is set, then the solutions, each followed by a blank line.
+pub fn ascii_solutions(game: &Game, solutions: &[DMatrix<u32>], piece_list: bool) -> String {
+    let mut text = String::new();
+    if piece_list {
+        for (id, piece) in game.pieces.iter().enumerate() {
+            text.push_str(&format!("{}: {}\n", ascii_letter(id), piece_label(piece)));
+            text.push_str(&ascii(&piece.matrix));
+            text.push('\n');
+        }
+    }
+
+    for solution in solutions {
+        text.push_str(&ascii(solution));
+        text.push('\n');
+    }
+    text
+}
+
+pub fn print_ascii(game: &Game, solutions: &[DMatrix<u32>], piece_list: bool) {
+    print!("{}", ascii_solutions(game, solutions, piece_list));
+    // On the standard error, so that a single solution can be read back as a board file
+    eprintln!("{}x{}: {} solutions", game.rows(), game.columns, solutions.len());
+}
+
+/// Returns the name of a piece of the original game, or its size for custom pieces.
+fn piece_label(piece: &Piece) -> String {
+    PieceName::from_matrix(&piece.matrix)
+        .map_or_else(|| format!("{} cells", piece.cells()), |name| format!("{name:?}"))
+}
+
+/// Game and solutions, as printed by the JSON output.
+#[derive(Serialize)]
+struct JsonOutput {
+    columns: u32,
+    rows: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pieces: Option<Vec<JsonPiece>>,
+    /// Each solution is a grid of piece ids, `null` for empty cells.
+    solutions: Vec<Vec<Vec<Option<usize>>>>,
+}
+
+#[derive(Serialize)]
+struct JsonPiece {
+    id: usize,
+    /// Name of the piece in the original game, if recognized.
+    name: Option<String>,
+    /// Cells of the piece, 1 when occupied.
+    matrix: Vec<Vec<u32>>,
+}
+
+/// Returns the rows of a matrix, mapping each cell with `cell`.
+pub fn rows<T>(matrix: &DMatrix<u32>, cell: impl Fn(u32) -> T) -> Vec<Vec<T>> {
+    matrix
+        .row_iter()
+        .map(|row| row.iter().map(|&value| cell(value)).collect())
+        .collect()
+}
+
+pub fn print_json(game: &Game, solutions: &[DMatrix<u32>], piece_list: bool) {
+    let pieces = piece_list.then(|| {
+        game.pieces
+            .iter()
+            .enumerate()
+            .map(|(id, piece)| JsonPiece {
+                id,
+                name: PieceName::from_matrix(&piece.matrix).map(|name| format!("{name:?}")),
+                matrix: rows(&piece.matrix, |cell| cell),
+            })
+            .collect()
+    });
+    let output = JsonOutput {
+        columns: game.columns,
+        rows: game.rows(),
+        pieces,
+        solutions: solutions.iter().map(|solution| rows(solution, piece_index)).collect(),
+    };
+    println!("{}", serde_json::to_string_pretty(&output).expect("Cannot serialize solutions"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtp_lib::game_resolver::with_piece_index;
+
+    #[test]
+    fn test_ascii() {
+        let matrix = DMatrix::from_row_slice(2, 2, &[
+            with_piece_index(0xFF_0000, 0), with_piece_index(0x00_FF00, 1),
+            0, with_piece_index(0x00_FF00, 1),
+        ]);
+        assert_eq!(ascii(&matrix), "AB\n.B\n");
+        assert_eq!(ascii(&DMatrix::from_row_slice(1, 2, &[1, 0])), "#.\n");
+    }
+}
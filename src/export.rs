@@ -0,0 +1,222 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use gtp_lib::game_resolver::piece_index;
+#[cfg(feature = "png")]
+use gtp_lib::png_renderer::png_from_svg;
+use gtp_lib::svg_renderer::{svg_from_matrix_with_options, SvgOptions};
+use gtp_lib::{Game, PieceName};
+use nalgebra::DMatrix;
+use serde::Serialize;
+
+use crate::cli::ExportFormat;
+use crate::display::rows;
+
+/// Name of the file listing the game and its exported solutions.
+pub const INDEX_FILE: &str = "index.json";
+/// Minimum number of digits of the solution file names, so that they sort in order.
+const MIN_INDEX_DIGITS: usize = 4;
+/// Letter standing for a piece that is not part of the original game in game codes.
+const UNKNOWN_PIECE_LETTER: char = '_';
+
+impl ExportFormat {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+            Self::Json => "json",
+        }
+    }
+}
+
+fn piece_letter(piece: &gtp_lib::Piece) -> char {
+    PieceName::from_matrix(&piece.matrix).map_or(UNKNOWN_PIECE_LETTER, PieceName::letter)
+}
+
+/// Sorts the pieces of `game` by their letter, so that the same piece set always gives
+/// the same game code, the same piece ids and the same solutions.
+pub fn canonical(mut game: Game) -> Game {
+    game.pieces.sort_by_key(piece_letter);
+    game
+}
+
+/// Returns the code of a game: its number of columns and the letters of its pieces, such as `5-ACDGH`.
+pub fn game_code(game: &Game) -> String {
+    let mut letters: Vec<char> = game.pieces.iter().map(piece_letter).collect();
+    letters.sort_unstable();
+    format!("{}-{}", game.columns, letters.into_iter().collect::<String>())
+}
+
+/// The index file of an export.
+#[derive(Serialize)]
+struct ExportIndex {
+    code: String,
+    columns: u32,
+    rows: u32,
+    pieces: Vec<IndexPiece>,
+    count: usize,
+    files: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct IndexPiece {
+    id: usize,
+    name: Option<String>,
+    cells: u32,
+}
+
+/// A solution file of the `json` format.
+#[derive(Serialize)]
+struct SolutionFile {
+    index: usize,
+    /// Piece ids per cell.
+    solution: Vec<Vec<Option<usize>>>,
+}
+
+/// Returns the name of the file of the solution at `index` (starting at 1).
+fn file_name(index: usize, count: usize, format: ExportFormat) -> String {
+    let digits = count.to_string().len().max(MIN_INDEX_DIGITS);
+    format!("{index:0digits$}.{}", format.extension())
+}
+
+/// Returns whether `name` is the name of a file written by a previous export.
+fn is_export_file(name: &str) -> bool {
+    name == INDEX_FILE
+        || name.split_once('.').is_some_and(|(stem, extension)| {
+            !stem.is_empty()
+                && stem.bytes().all(|byte| byte.is_ascii_digit())
+                && matches!(extension, "svg" | "png" | "json")
+        })
+}
+
+/// Renders one solution in the export format.
+fn render(solution: &DMatrix<u32>, index: usize, format: ExportFormat, style: SvgOptions) -> io::Result<Vec<u8>> {
+    match format {
+        ExportFormat::Svg => Ok(format!("{}\n", svg_from_matrix_with_options(solution, &style)).into_bytes()),
+        #[cfg(feature = "png")]
+        ExportFormat::Png => png_from_svg(&svg_from_matrix_with_options(solution, &style), 10.0).map_err(io::Error::other),
+        #[cfg(not(feature = "png"))]
+        ExportFormat::Png => Err(io::Error::other("PNG output requires the `png` feature")),
+        ExportFormat::Json => {
+            let file = SolutionFile { index, solution: rows(solution, piece_index) };
+            let mut json = serde_json::to_vec_pretty(&file)?;
+            json.push(b'\n');
+            Ok(json)
+        }
+    }
+}
+
+/// Writes each solution of `game` to `<dir>/<game-code>/<index>.<format>`, with an index file.
+///
+/// Files left by a previous export of the same game are removed first, so that repeated runs
+/// give the same directory content. Returns the directory of the game.
+pub fn export(
+    dir: &Path,
+    game: &Game,
+    solutions: &[DMatrix<u32>],
+    format: ExportFormat,
+    style: SvgOptions,
+) -> io::Result<PathBuf> {
+    let code = game_code(game);
+    let game_dir = dir.join(&code);
+    fs::create_dir_all(&game_dir)?;
+    for entry in fs::read_dir(&game_dir)? {
+        let entry = entry?;
+        if entry.file_name().to_str().is_some_and(is_export_file) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    let mut files = Vec::with_capacity(solutions.len());
+    for (position, solution) in solutions.iter().enumerate() {
+        let index = position + 1;
+        let name = file_name(index, solutions.len(), format);
+        fs::write(game_dir.join(&name), render(solution, index, format, style)?)?;
+        files.push(name);
+    }
+
+    let index = ExportIndex {
+        code,
+        columns: game.columns,
+        rows: game.rows(),
+        pieces: game
+            .pieces
+            .iter()
+            .enumerate()
+            .map(|(id, piece)| IndexPiece {
+                id,
+                name: PieceName::from_matrix(&piece.matrix).map(|name| format!("{name:?}")),
+                cells: piece.cells(),
+            })
+            .collect(),
+        count: solutions.len(),
+        files,
+    };
+    let mut json = serde_json::to_vec_pretty(&index)?;
+    json.push(b'\n');
+    fs::write(game_dir.join(INDEX_FILE), json)?;
+
+    Ok(game_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtp_lib::{GameResolver, GameResolverTrait};
+
+    fn game() -> Game {
+        Game {
+            columns: 3,
+            pieces: vec![PieceName::BrownL3.piece(), PieceName::RedSquare1.piece(), PieceName::TanBar2.piece()],
+        }
+    }
+
+    #[test]
+    fn test_game_code() {
+        assert_eq!(game_code(&game()), "3-ABC");
+        let canonical = canonical(game());
+        assert_eq!(PieceName::from_matrix(&canonical.pieces[0].matrix), Some(PieceName::RedSquare1));
+        assert_eq!(PieceName::from_matrix(&canonical.pieces[2].matrix), Some(PieceName::BrownL3));
+    }
+
+    #[test]
+    fn test_file_names() {
+        assert_eq!(file_name(7, 12, ExportFormat::Svg), "0007.svg");
+        assert_eq!(file_name(7, 12_345, ExportFormat::Json), "00007.json");
+        assert!(is_export_file("0007.png"));
+        assert!(is_export_file(INDEX_FILE));
+        assert!(!is_export_file("notes.json"));
+        assert!(!is_export_file(".svg"));
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = std::env::temp_dir().join(format!("gtp-export-test-{}", std::process::id()));
+        let game = canonical(game());
+        let solutions = GameResolver.resolve(&game);
+        assert_eq!(solutions.len(), 12);
+
+        let game_dir = export(&dir, &game, &solutions, ExportFormat::Json, SvgOptions::default()).unwrap();
+        assert_eq!(game_dir, dir.join("3-ABC"));
+        let notes = game_dir.join("notes.txt");
+        fs::write(&notes, "kept").unwrap();
+
+        // A second export with fewer solutions removes the extra files, and only them
+        export(&dir, &game, &solutions[..2], ExportFormat::Svg, SvgOptions::default()).unwrap();
+        let mut names: Vec<String> = fs::read_dir(&game_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["0001.svg", "0002.svg", INDEX_FILE, "notes.txt"]);
+
+        let index: serde_json::Value = serde_json::from_slice(&fs::read(game_dir.join(INDEX_FILE)).unwrap()).unwrap();
+        assert_eq!(index["code"], "3-ABC");
+        assert_eq!(index["rows"], 2);
+        assert_eq!(index["count"], 2);
+        assert_eq!(index["pieces"][1]["name"], "TanBar2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,388 @@
+use std::io;
+
+use gtp_lib::bitboard::fits;
+use gtp_lib::game_resolver::{piece_index, with_piece_index};
+use gtp_lib::matrix_tools::{flip_matrix, rotate_matrix};
+use gtp_lib::{Game, HintEngine, PieceName, PlayError, PlayEvent, PlaySession};
+use nalgebra::DMatrix;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::display::{from_rgb_u32, BLOCK_CHAR, DEFAULT_COLOR, DISPLAY_SIZE};
+
+/// Character drawn for the cells of the piece being moved.
+const HELD_CHAR: &str = "▒";
+/// Color of the held piece where it overlaps a placed piece.
+const COLLISION_COLOR: Color = Color::Red;
+
+/// State of the play mode: the game being played and the position of the piece in hand.
+pub struct Play {
+    session: PlaySession,
+    /// Top left corner of the held piece on the board.
+    row: usize,
+    col: usize,
+    message: String,
+    quit: bool,
+}
+
+impl Play {
+    /// Starts a game with an empty board, holding the first piece.
+    ///
+    /// The game must be valid, see [`Game::validate`].
+    pub fn new(game: Game) -> Self {
+        let session = PlaySession::new(game).expect("Invalid game");
+        let mut play = Self {
+            session,
+            row: 0,
+            col: 0,
+            message: "Place all the pieces to fill the board".to_string(),
+            quit: false,
+        };
+        play.hold_next(0);
+        play
+    }
+
+    fn game(&self) -> &Game {
+        self.session.game()
+    }
+
+    /// Returns the held piece and its shape.
+    fn held(&self) -> Option<(usize, &DMatrix<u32>)> {
+        let (piece, orientation) = self.session.held()?;
+        Some((piece, &self.session.orientations(piece)?[orientation]))
+    }
+
+    /// Returns the placed pieces, encoded like the solutions of the solver with their terminal colors.
+    fn board(&self) -> DMatrix<u32> {
+        self.session.board().map(|cell| {
+            piece_index(cell).map_or(0, |piece| with_piece_index(self.game().pieces[piece].tui_color, piece))
+        })
+    }
+
+    fn piece_name(&self, piece: usize) -> String {
+        PieceName::from_matrix(&self.game().pieces[piece].matrix)
+            .map_or_else(|| format!("piece {}", piece + 1), |name| name.description().to_lowercase())
+    }
+
+    /// Takes `piece` in hand in its first orientation, at the top left of the board.
+    fn hold(&mut self, piece: usize) {
+        if self.session.hold(piece, 0).is_ok() {
+            self.row = 0;
+            self.col = 0;
+        }
+    }
+
+    /// Takes the first piece not on the board, starting from `start` and wrapping around.
+    fn hold_next(&mut self, start: usize) {
+        let count = self.game().pieces.len();
+        if let Some(piece) = (0..count).map(|offset| (start + offset) % count).find(|&piece| !self.session.is_placed(piece)) {
+            self.hold(piece);
+        }
+    }
+
+    /// Takes the next (`forward`) or previous piece not on the board.
+    fn cycle(&mut self, forward: bool) {
+        let Some((held, _)) = self.session.held() else { return };
+        let count = self.game().pieces.len();
+        let start = if forward { held + 1 } else { held + count - 1 };
+        let step = if forward { 1 } else { count - 1 };
+        let next = (0..count).map(|offset| (start + offset * step) % count).find(|&piece| !self.session.is_placed(piece));
+        if let Some(piece) = next {
+            self.hold(piece);
+        }
+    }
+
+    /// Rotates (or flips) the held piece, keeping it on the board.
+    fn reorient(&mut self, flip: bool) {
+        let Some((_, shape)) = self.held() else { return };
+        let (rows, cols) = (self.session.rows(), self.session.columns());
+        let shape = if flip { flip_matrix(shape) } else { rotate_matrix(shape) };
+        if !fits(&shape, rows, cols, 0, 0) {
+            self.message = "The piece does not fit on the board that way".to_string();
+            return;
+        }
+        let turned = if flip { self.session.flip() } else { self.session.rotate() };
+        if turned.is_ok() {
+            self.row = self.row.min(rows - shape.nrows());
+            self.col = self.col.min(cols - shape.ncols());
+        }
+    }
+
+    fn move_by(&mut self, rows: isize, cols: isize) {
+        let Some((_, shape)) = self.held() else { return };
+        let (Some(row), Some(col)) = (self.row.checked_add_signed(rows), self.col.checked_add_signed(cols)) else {
+            return;
+        };
+        if fits(shape, self.session.rows(), self.session.columns(), row, col) {
+            self.row = row;
+            self.col = col;
+        }
+    }
+
+    /// Puts the held piece on the board, unless it overlaps a placed piece.
+    fn place(&mut self) {
+        match self.session.place_held(self.row, self.col) {
+            Ok(PlayEvent::Completed(_)) => self.message = "Board complete, well done!".to_string(),
+            Ok(event) => {
+                if let PlayEvent::Placed(placement) = event {
+                    self.hold_next(placement.piece + 1);
+                }
+                self.message = format!("{} pieces left", self.session.remaining().len());
+            }
+            Err(PlayError::Overlaps { .. }) => self.message = "The piece overlaps another piece".to_string(),
+            Err(_) => {}
+        }
+    }
+
+    /// Takes the last placed piece back in hand.
+    fn undo(&mut self) {
+        match self.session.undo() {
+            Ok(PlayEvent::Removed(placement)) => {
+                self.row = placement.row;
+                self.col = placement.col;
+                self.message = format!("Took back the {}", self.piece_name(placement.piece));
+            }
+            Ok(_) => {}
+            Err(_) => self.message = "Nothing to undo".to_string(),
+        }
+    }
+
+    /// Asks the solver for a completion of the board, and moves the held piece to its place in it.
+    fn hint(&mut self) {
+        let Some((piece, _)) = self.session.held() else { return };
+        let Ok(engine) = HintEngine::new(self.game(), &self.session.board()) else { return };
+        let Some(solution) = engine.completion() else {
+            let blocking: Vec<String> = engine.blocking_pieces().into_iter().map(|piece| self.piece_name(piece)).collect();
+            self.message = if blocking.is_empty() {
+                "No solution from here, undo some pieces (u)".to_string()
+            } else {
+                format!("No solution from here, the {} is in the way (u to undo)", blocking.join(" or the "))
+            };
+            return;
+        };
+
+        let Some(placement) = self.game().placements(&solution).ok().and_then(|placements| placements.get(piece).copied()) else {
+            return;
+        };
+        if self.session.hold(piece, placement.orientation).is_ok() {
+            self.row = placement.row;
+            self.col = placement.col;
+            self.message = format!("Hint: the {} goes here, press Enter to place it", self.piece_name(piece));
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up => self.move_by(-1, 0),
+            KeyCode::Down => self.move_by(1, 0),
+            KeyCode::Left => self.move_by(0, -1),
+            KeyCode::Right => self.move_by(0, 1),
+            KeyCode::Char('r') => self.reorient(false),
+            KeyCode::Char('f') => self.reorient(true),
+            KeyCode::Tab => self.cycle(true),
+            KeyCode::BackTab => self.cycle(false),
+            KeyCode::Enter | KeyCode::Char(' ') => self.place(),
+            KeyCode::Char('u') | KeyCode::Backspace => self.undo(),
+            KeyCode::Char('h') => self.hint(),
+            _ => {}
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, message, help] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1), Constraint::Length(1)]).areas(frame.area());
+        let [pieces, board] = Layout::horizontal([Constraint::Length(34), Constraint::Min(0)]).areas(main);
+
+        let items: Vec<ListItem> = self
+            .game()
+            .pieces
+            .iter()
+            .enumerate()
+            .map(|(index, piece)| {
+                let (r, g, b) = from_rgb_u32(piece.tui_color);
+                let marker = if self.session.held().is_some_and(|(held, _)| held == index) {
+                    "> "
+                } else if self.session.is_placed(index) {
+                    "✓ "
+                } else {
+                    "  "
+                };
+                let item = ListItem::new(Line::from(vec![
+                    Span::raw(marker),
+                    Span::styled(BLOCK_CHAR.repeat(DISPLAY_SIZE * 2), Style::new().fg(Color::Rgb(r, g, b))),
+                    Span::raw(format!(" {}", self.piece_name(index))),
+                ]));
+                if self.session.is_placed(index) { item.style(Style::new().add_modifier(Modifier::DIM)) } else { item }
+            })
+            .collect();
+        frame.render_widget(List::new(items).block(Block::bordered().title(" Pieces ")), pieces);
+
+        let title = format!(" {}x{} board ", self.session.rows(), self.session.columns());
+        frame.render_widget(Paragraph::new(self.board_lines()).block(Block::bordered().title(title)), board);
+
+        let style = if self.session.is_complete() { Style::new().fg(Color::Green) } else { Style::new() };
+        frame.render_widget(Paragraph::new(self.message.as_str()).style(style), message);
+        frame.render_widget(
+            Paragraph::new("←↑↓→ move  r rotate  f flip  Tab piece  Enter place  u undo  h hint  q quit")
+                .style(Style::new().add_modifier(Modifier::DIM)),
+            help,
+        );
+    }
+
+    /// Renders the board as colored blocks, with the held piece over the placed ones.
+    fn board_lines(&self) -> Vec<Line<'static>> {
+        let board = self.board();
+        let held_cells: Vec<(usize, usize)> = self.held().map_or_else(Vec::new, |(_, shape)| {
+            cells(shape).map(|(r, c)| (self.row + r, self.col + c)).collect()
+        });
+        let held_color = self.held().map(|(piece, _)| self.game().pieces[piece].tui_color);
+
+        (0..self.session.rows())
+            .flat_map(|row| {
+                let spans: Vec<Span> = (0..self.session.columns())
+                    .map(|col| {
+                        let cell = board[(row, col)];
+                        match held_color.filter(|_| held_cells.contains(&(row, col))) {
+                            Some(_) if cell != 0 => {
+                                Span::styled(HELD_CHAR.repeat(DISPLAY_SIZE * 2), Style::new().fg(COLLISION_COLOR))
+                            }
+                            Some(color) => Span::styled(HELD_CHAR.repeat(DISPLAY_SIZE * 2), Style::new().fg(rgb(color))),
+                            None => Span::styled(
+                                BLOCK_CHAR.repeat(DISPLAY_SIZE * 2),
+                                Style::new().fg(rgb(if cell > 0 { cell } else { DEFAULT_COLOR })),
+                            ),
+                        }
+                    })
+                    .collect();
+                std::iter::repeat_n(Line::from(spans), DISPLAY_SIZE)
+            })
+            .collect()
+    }
+}
+
+fn rgb(color: u32) -> Color {
+    let (r, g, b) = from_rgb_u32(color);
+    Color::Rgb(r, g, b)
+}
+
+/// Returns the coordinates of the occupied cells of a piece matrix.
+fn cells(shape: &DMatrix<u32>) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..shape.nrows())
+        .flat_map(move |r| (0..shape.ncols()).map(move |c| (r, c)))
+        .filter(|&cell| shape[cell] != 0)
+}
+
+/// Runs the play mode until the user quits.
+pub fn run(game: Game) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = run_play(&mut terminal, Play::new(game));
+    ratatui::restore();
+    result
+}
+
+fn run_play(terminal: &mut DefaultTerminal, mut play: Play) -> io::Result<()> {
+    while !play.quit {
+        terminal.draw(|frame| play.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                play.handle_key(key.code);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 board with a 1x1 piece and an L.
+    fn play() -> Play {
+        Play::new(Game {
+            columns: 2,
+            pieces: vec![PieceName::RedSquare1.piece(), PieceName::BrownL3.piece()],
+        })
+    }
+
+    #[test]
+    fn test_place_rejects_overlaps() {
+        let mut play = play();
+        play.handle_key(KeyCode::Enter);
+        assert_eq!(play.session.placements().len(), 1);
+        assert_eq!(play.session.held().map(|(piece, _)| piece), Some(1));
+
+        // The L, held at the top left, covers the red square
+        play.handle_key(KeyCode::Enter);
+        assert_eq!(play.session.placements().len(), 1);
+        assert_eq!(play.message, "The piece overlaps another piece");
+    }
+
+    #[test]
+    fn test_move_stays_on_board() {
+        let mut play = play();
+        play.handle_key(KeyCode::Left);
+        play.handle_key(KeyCode::Down);
+        play.handle_key(KeyCode::Down);
+        assert_eq!((play.row, play.col), (1, 0));
+    }
+
+    #[test]
+    fn test_complete_and_undo() {
+        let mut play = play();
+        play.handle_key(KeyCode::Enter);
+        // Flip the L so that its missing cell is at the top left
+        play.handle_key(KeyCode::Char('f'));
+        play.handle_key(KeyCode::Enter);
+        assert!(play.session.is_complete());
+        assert!(play.session.held().is_none());
+        assert_eq!(play.message, "Board complete, well done!");
+
+        play.handle_key(KeyCode::Char('u'));
+        assert!(!play.session.is_complete());
+        assert_eq!(play.session.held().map(|(piece, _)| piece), Some(1));
+    }
+
+    #[test]
+    fn test_hint() {
+        let mut play = play();
+        play.handle_key(KeyCode::Tab);
+        play.handle_key(KeyCode::Char('h'));
+        // The hinted placement must leave room for the red square
+        play.handle_key(KeyCode::Enter);
+        play.handle_key(KeyCode::Char('h'));
+        play.handle_key(KeyCode::Enter);
+        assert!(play.session.is_complete());
+    }
+
+    #[test]
+    fn test_hint_on_dead_end() {
+        // The L in the middle row of a 3x2 board, with a 1x3 bar left to place
+        let mut play = Play::new(Game {
+            columns: 2,
+            pieces: vec![PieceName::BrownL3.piece(), PieceName::OrangeBar3.piece()],
+        });
+        play.handle_key(KeyCode::Down);
+        play.handle_key(KeyCode::Enter);
+        play.handle_key(KeyCode::Char('h'));
+        assert_eq!(play.message, "No solution from here, undo some pieces (u)");
+    }
+
+    #[test]
+    fn test_hint_names_blocking_piece() {
+        // The tan bar across the middle row of a 3x2 board leaves no room for the L
+        let mut play = Play::new(Game {
+            columns: 2,
+            pieces: vec![PieceName::TanBar2.piece(), PieceName::BrownL3.piece(), PieceName::RedSquare1.piece()],
+        });
+        play.handle_key(KeyCode::Char('r'));
+        play.handle_key(KeyCode::Down);
+        play.handle_key(KeyCode::Enter);
+        play.handle_key(KeyCode::Char('h'));
+        assert_eq!(play.message, "No solution from here, the tan bar is in the way (u to undo)");
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use gtp_lib::bitboard::generate_positions;
+use gtp_lib::dead_end;
+use gtp_lib::game_resolver::with_piece_index;
+use gtp_lib::matrix_tools::rotation_variants;
+use gtp_lib::svg_renderer::{svg_contact_sheet, svg_from_matrix_with_options, ContactSheetOptions};
+use gtp_lib::{Game, Piece, PieceName};
+use nalgebra::DMatrix;
+
+use crate::export::game_code;
+
+/// Style sheet of the report, inlined so that the page needs no other file.
+const STYLE: &str = "\
+body{font-family:sans-serif;margin:2em auto;max-width:60em;padding:0 1em;color:#222}
+h1{font-size:1.6em}
+table{border-collapse:collapse;margin-bottom:1.5em}
+th,td{padding:.3em .8em;text-align:left;border-bottom:1px solid #ddd}
+td.number{text-align:right}
+.legend .shape{display:inline-block;vertical-align:middle;width:calc(var(--columns) * 1.2em)}
+.legend svg{width:100%;height:auto;display:block}
+.sheet svg{width:100%;height:auto}
+footer{margin-top:2em;font-size:.8em;color:#777}
+";
+
+/// Statistics of the solver run shown in the report.
+#[derive(Debug, Clone, Copy)]
+pub struct SolverStats {
+    /// Time taken to find the solutions.
+    pub duration: Duration,
+    /// Maximum number of solutions that were searched for, if any.
+    pub limit: Option<usize>,
+}
+
+/// Escapes the characters of `text` that have a meaning in HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Returns the number of distinct orientations of `piece`, and the number of ways to place them on the board.
+fn placements(piece: &Piece, rows: usize, columns: usize) -> (usize, usize) {
+    let variants = rotation_variants(&piece.matrix);
+    let positions = variants
+        .iter()
+        .map(|matrix| generate_positions(&Piece { matrix: matrix.clone(), ..piece.clone() }, rows, columns).len())
+        .sum();
+    (variants.len(), positions)
+}
+
+/// Renders a self-contained HTML page showing `solutions` of `game` on a contact sheet.
+///
+/// The page lists the pieces of the game, the board dimensions, the number of solutions and
+/// the solver statistics. Every image is an inline SVG, so the page can be viewed offline.
+pub fn html_report(game: &Game, solutions: &[DMatrix<u32>], stats: SolverStats, options: &ContactSheetOptions) -> String {
+    let rows = game.rows() as usize;
+    let columns = game.columns as usize;
+    let title = format!("Gagne Ton Papa! {}", game_code(game));
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+        title = escape(&title)
+    );
+
+    html.push_str("<h2>Game</h2>\n<table class=\"summary\">\n");
+    let _ = writeln!(html, "<tr><th>Board</th><td>{columns} columns &times; {rows} rows ({} cells)</td></tr>", game.cells());
+    let _ = writeln!(html, "<tr><th>Pieces</th><td>{}</td></tr>", game.pieces.len());
+    let _ = writeln!(html, "<tr><th>Solutions</th><td>{}</td></tr>", solution_count(solutions.len(), stats.limit));
+    let _ = writeln!(html, "<tr><th>Solving time</th><td>{:.1} ms</td></tr>", stats.duration.as_secs_f64() * 1000.0);
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Pieces</h2>\n<table class=\"legend\">\n");
+    html.push_str("<tr><th></th><th>Piece</th><th>Cells</th><th>Orientations</th><th>Placements</th></tr>\n");
+    // Legend cells have the same width, set from the number of columns of each piece
+    for (id, piece) in game.pieces.iter().enumerate() {
+        let name = PieceName::from_matrix(&piece.matrix);
+        let shape = piece.matrix.map(|cell| if cell == 0 { 0 } else { with_piece_index(piece.color, id) });
+        let (orientations, positions) = placements(piece, rows, columns);
+        let _ = writeln!(
+            html,
+            "<tr><td><div class=\"shape\" style=\"--columns:{}\">{}</div></td><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{orientations}</td>\
+             <td class=\"number\">{positions}</td></tr>",
+            piece.matrix.ncols(),
+            svg_from_matrix_with_options(&shape, &options.style),
+            escape(name.map_or("Custom piece", PieceName::description)),
+            piece.cells()
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Solutions</h2>\n");
+    if solutions.is_empty() {
+        html.push_str("<p>This game has no solution.</p>\n");
+        if game.is_valid() && stats.limit != Some(0) {
+            let reason = dead_end::explain_unsolvable(game, 0, &game.piece_ids());
+            let _ = writeln!(html, "<p>Reason: {}.</p>", escape(&reason.to_string()));
+        }
+    } else {
+        let _ = writeln!(html, "<div class=\"sheet\">\n{}\n</div>", svg_contact_sheet(solutions, options));
+    }
+
+    let _ = write!(html, "<footer>Generated by gagne-ton-papa {}</footer>\n</body>\n</html>\n", env!("CARGO_PKG_VERSION"));
+    html
+}
+
+/// Describes the number of solutions, noting when the search stopped at the limit.
+fn solution_count(count: usize, limit: Option<usize>) -> String {
+    match limit {
+        Some(limit) if count >= limit => format!("{count} shown (search stopped at --limit {limit})"),
+        _ => count.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtp_lib::{GameResolver, GameResolverTrait};
+
+    fn game() -> Game {
+        Game {
+            columns: 3,
+            pieces: vec![PieceName::RedSquare1.piece(), PieceName::TanBar2.piece(), PieceName::BrownL3.piece()],
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+
+    #[test]
+    fn test_placements() {
+        // On a 2x3 board, the L fits in its 4 orientations at 2 columns each
+        assert_eq!(placements(&PieceName::BrownL3.piece(), 2, 3), (4, 8));
+        assert_eq!(placements(&PieceName::RedSquare1.piece(), 2, 3), (1, 6));
+    }
+
+    #[test]
+    fn test_html_report() {
+        let game = game();
+        let solutions = GameResolver.resolve(&game);
+        let stats = SolverStats { duration: Duration::from_millis(3), limit: None };
+        let html = html_report(&game, &solutions, stats, &ContactSheetOptions::default());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Gagne Ton Papa! 3-ABC</title>"));
+        assert!(html.contains("3 columns &times; 2 rows (6 cells)"));
+        assert!(html.contains("<tr><th>Solutions</th><td>12</td></tr>"));
+        assert!(html.contains("3.0 ms"));
+        // One SVG per piece, and the contact sheet
+        assert_eq!(html.matches("<svg").count(), 4);
+        // Nothing is loaded from elsewhere
+        assert!(!html.contains("src="));
+        assert!(!html.contains("href="));
+    }
+
+    #[test]
+    fn test_html_report_limited_and_empty() {
+        assert_eq!(solution_count(5, Some(5)), "5 shown (search stopped at --limit 5)");
+        assert_eq!(solution_count(3, Some(5)), "3");
+
+        let game = Game { columns: 2, ..game() };
+        let stats = SolverStats { duration: Duration::ZERO, limit: None };
+        let html = html_report(&game, &[], stats, &ContactSheetOptions::default());
+        assert!(html.contains("This game has no solution."));
+
+        // A 1x3 bar on a 2x2 board
+        let game = Game { columns: 2, pieces: vec![PieceName::OrangeBar3.piece(), PieceName::RedSquare1.piece()] };
+        let html = html_report(&game, &[], stats, &ContactSheetOptions::default());
+        assert!(html.contains("<p>Reason: piece 0 is too large for the board.</p>"));
+    }
+}
@@ -0,0 +1,369 @@
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use gtp_lib::game_resolver::piece_index;
+use gtp_lib::palette::Palette;
+use gtp_lib::svg_renderer::svg_from_matrix;
+use gtp_lib::{Game, PieceName, PlacementTable};
+use nalgebra::DMatrix;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::batch::PieceRef;
+use crate::board_file::{Board, BoardError};
+use crate::display::{ansi, rows};
+
+/// Number of games whose placement tables are kept between calls.
+const CACHE_SIZE: usize = 16;
+/// Number of solutions of a page when `size` is not given.
+const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// Error codes defined by the JSON-RPC 2.0 specification.
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i32, message: impl std::fmt::Display) -> Self {
+        Self { code, message: message.to_string() }
+    }
+
+    fn invalid_params(message: impl std::fmt::Display) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+#[derive(Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+    id: Value,
+}
+
+impl Response {
+    fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self { jsonrpc: "2.0", result, error, id }
+    }
+}
+
+/// Parameters selecting a game, shared by `solve`, `count` and `page`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GameParams {
+    columns: u32,
+    pieces: Vec<PieceRef>,
+    #[serde(default)]
+    palette: Option<String>,
+    /// Maximum number of solutions of `solve`.
+    #[serde(default)]
+    limit: Option<usize>,
+    /// Page index of `page`, starting at 0.
+    #[serde(default)]
+    page: Option<usize>,
+    /// Page size of `page`.
+    #[serde(default)]
+    size: Option<usize>,
+}
+
+/// Parameters of `verify` and `render`: a board file, and optionally its game.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoardParams {
+    /// Board in the format of board files, one line per row.
+    board: String,
+    #[serde(default)]
+    columns: Option<u32>,
+    #[serde(default)]
+    pieces: Option<Vec<PieceRef>>,
+    #[serde(default)]
+    palette: Option<String>,
+    /// Output of `render`: `svg` (default) or `ansi`.
+    #[serde(default)]
+    format: Option<String>,
+}
+
+fn parse_palette(name: Option<&str>) -> Result<Palette, RpcError> {
+    name.map_or(Ok(Palette::Classic), |name| {
+        Palette::from_name(name).ok_or_else(|| RpcError::invalid_params(format_args!("unknown palette '{name}'")))
+    })
+}
+
+fn parse_pieces(pieces: &[PieceRef]) -> Result<Vec<gtp_lib::Piece>, RpcError> {
+    pieces.iter().map(PieceRef::piece).collect::<Result<_, _>>().map_err(RpcError::invalid_params)
+}
+
+/// Identifies a game in the cache: its columns, pieces in order, and palette.
+#[derive(PartialEq, Eq)]
+struct GameKey {
+    columns: u32,
+    pieces: Vec<Option<PieceName>>,
+    palette: Palette,
+}
+
+/// Answers JSON-RPC requests, keeping the placement tables of the last games between calls.
+#[derive(Default)]
+pub struct RpcServer {
+    /// Most recently used last.
+    cache: Vec<(GameKey, Rc<PlacementTable>)>,
+}
+
+impl RpcServer {
+    /// Returns the game of `params` and its placement table, computing it if it is not cached.
+    fn table(&mut self, params: &GameParams) -> Result<(Game, Rc<PlacementTable>), RpcError> {
+        let palette = parse_palette(params.palette.as_deref())?;
+        let mut game = Game { columns: params.columns, pieces: parse_pieces(&params.pieces)? };
+        game.validate().map_err(|error| RpcError::invalid_params(format_args!("invalid game: {error}")))?;
+        palette.apply(&mut game);
+
+        let key = GameKey {
+            columns: game.columns,
+            pieces: game.pieces.iter().map(|piece| PieceName::from_matrix(&piece.matrix)).collect(),
+            palette,
+        };
+        let table = match self.cache.iter().position(|(cached, _)| *cached == key) {
+            Some(index) => self.cache.remove(index).1,
+            None => {
+                if self.cache.len() == CACHE_SIZE {
+                    self.cache.remove(0);
+                }
+                Rc::new(PlacementTable::new(&game))
+            }
+        };
+        self.cache.push((key, Rc::clone(&table)));
+        Ok((game, table))
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "solve" => {
+                let params: GameParams = parse_params(params)?;
+                let (_, table) = self.table(&params)?;
+                let solutions = table.page(0, params.limit.unwrap_or(usize::MAX));
+                Ok(json!({ "count": solutions.len(), "solutions": grids(&solutions) }))
+            }
+            "count" => {
+                let params: GameParams = parse_params(params)?;
+                let (_, table) = self.table(&params)?;
+                Ok(json!({ "count": table.count() }))
+            }
+            "page" => {
+                let params: GameParams = parse_params(params)?;
+                let (page, size) = (params.page.unwrap_or(0), params.size.unwrap_or(DEFAULT_PAGE_SIZE));
+                let (_, table) = self.table(&params)?;
+                Ok(json!({ "page": page, "size": size, "solutions": grids(&table.page(page, size)) }))
+            }
+            "verify" => verify(&parse_params(params)?),
+            "render" => render(&parse_params(params)?),
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format_args!("unknown method '{method}'"))),
+        }
+    }
+
+    /// Answers one request object, or returns `None` for a notification.
+    fn handle(&mut self, request: Value) -> Option<Response> {
+        let id = request.get("id").cloned();
+        let Value::Object(mut request) = request else {
+            return Some(Response::new(Value::Null, Err(RpcError::new(INVALID_REQUEST, "a request must be an object"))));
+        };
+        if request.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+            return Some(Response::new(id.unwrap_or_default(), Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""))));
+        }
+        let Some(Value::String(method)) = request.remove("method") else {
+            return Some(Response::new(id.unwrap_or_default(), Err(RpcError::new(INVALID_REQUEST, "method must be a string"))));
+        };
+
+        let outcome = self.call(&method, request.remove("params").unwrap_or_else(|| json!({})));
+        id.map(|id| Response::new(id, outcome))
+    }
+
+    /// Answers one line of input, a request or a batch of requests.
+    ///
+    /// Returns the JSON response to write, or `None` when there is nothing to answer.
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let responses = match serde_json::from_str::<Value>(line) {
+            Err(error) => vec![Response::new(Value::Null, Err(RpcError::new(PARSE_ERROR, error)))],
+            Ok(Value::Array(batch)) if batch.is_empty() => {
+                vec![Response::new(Value::Null, Err(RpcError::new(INVALID_REQUEST, "empty batch")))]
+            }
+            Ok(Value::Array(batch)) => {
+                let responses: Vec<Response> = batch.into_iter().filter_map(|request| self.handle(request)).collect();
+                return (!responses.is_empty())
+                    .then(|| serde_json::to_string(&responses).expect("Cannot serialize responses"));
+            }
+            Ok(request) => self.handle(request).into_iter().collect(),
+        };
+        responses.first().map(|response| serde_json::to_string(response).expect("Cannot serialize response"))
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(RpcError::invalid_params)
+}
+
+/// Returns the piece ids per cell of each solution.
+fn grids(solutions: &[DMatrix<u32>]) -> Vec<Vec<Vec<Option<usize>>>> {
+    solutions.iter().map(|solution| rows(solution, piece_index)).collect()
+}
+
+/// Returns the game of a board: the given pieces, or the pieces recognized on the board.
+fn board_game(board: &Board, params: &BoardParams) -> Result<Result<Game, BoardError>, RpcError> {
+    let palette = parse_palette(params.palette.as_deref())?;
+    let pieces = params.pieces.as_deref().map(parse_pieces).transpose()?;
+    let columns = params.columns.unwrap_or_else(|| u32::try_from(board.columns()).expect("Column count too large"));
+    Ok(pieces.map_or_else(|| board.recognized_game(), |pieces| Ok(Game { columns: 0, pieces })).map(|game| {
+        let mut game = Game { columns, ..game };
+        palette.apply(&mut game);
+        game
+    }))
+}
+
+fn verify(params: &BoardParams) -> Result<Value, RpcError> {
+    if params.format.is_some() {
+        return Err(RpcError::invalid_params("unknown field `format`"));
+    }
+    let board = Board::parse(&params.board).map_err(RpcError::invalid_params)?;
+    match board_game(&board, params)?.and_then(|game| board.solution(&game, true)) {
+        Ok(_) => Ok(json!({ "valid": true, "error": null })),
+        Err(error) if error.is_format_error() => Err(RpcError::invalid_params(error)),
+        Err(error) => Ok(json!({ "valid": false, "error": error.to_string() })),
+    }
+}
+
+fn render(params: &BoardParams) -> Result<Value, RpcError> {
+    let board = Board::parse(&params.board).map_err(RpcError::invalid_params)?;
+    let solution = board_game(&board, params)?
+        .and_then(|game| board.solution(&game, false))
+        .map_err(RpcError::invalid_params)?;
+    let output = match params.format.as_deref() {
+        None | Some("svg") => svg_from_matrix(&solution),
+        Some("ansi") => ansi(&solution),
+        Some(format) => return Err(RpcError::invalid_params(format_args!("unknown format '{format}', expected svg or ansi"))),
+    };
+    Ok(Value::String(output))
+}
+
+/// Answers the requests of `input`, one JSON-RPC request or batch per line, until the end of the input.
+///
+/// Each response is written on its own line and flushed, so that clients can wait for it.
+pub fn run(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut server = RpcServer::default();
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_line(&line) {
+            writeln!(output, "{response}")?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME: &str = r#""columns": 5, "pieces": ["RedSquare1", "OrangeBar3", "BrownL3", "YellowZigZag4", "BlueT4"]"#;
+
+    fn call(server: &mut RpcServer, request: &str) -> Value {
+        serde_json::from_str(&server.handle_line(request).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_methods() {
+        let mut server = RpcServer::default();
+        let count = call(&mut server, &format!(r#"{{"jsonrpc": "2.0", "id": 1, "method": "count", "params": {{{GAME}}}}}"#));
+        assert_eq!(count, json!({ "jsonrpc": "2.0", "id": 1, "result": { "count": 36 } }));
+
+        let solve = call(&mut server, &format!(r#"{{"jsonrpc": "2.0", "id": 2, "method": "solve", "params": {{{GAME}, "limit": 2}}}}"#));
+        assert_eq!(solve["result"]["count"], 2);
+        assert_eq!(solve["result"]["solutions"][0].as_array().unwrap().len(), 3);
+
+        let page = call(&mut server, &format!(r#"{{"jsonrpc": "2.0", "id": "p", "method": "page", "params": {{{GAME}, "page": 3}}}}"#));
+        assert_eq!(page["id"], "p");
+        assert_eq!(page["result"]["solutions"].as_array().unwrap().len(), 6);
+        // The three calls share one placement table
+        assert_eq!(server.cache.len(), 1);
+
+        let verify = r#"{"jsonrpc": "2.0", "id": 4, "method": "verify", "params": {"board": "DDEEE\nADDEC\nBBBCC"}}"#;
+        assert_eq!(call(&mut server, verify)["result"], json!({ "valid": true, "error": null }));
+        let verify = r#"{"jsonrpc": "2.0", "id": 5, "method": "verify", "params": {"board": "AB\nBB", "pieces": [0, 1]}}"#;
+        assert_eq!(call(&mut server, verify)["result"]["valid"], false);
+
+        let render = r#"{"jsonrpc": "2.0", "id": 6, "method": "render", "params": {"board": "DDEEE\nADDEC\nBBBCC"}}"#;
+        assert!(call(&mut server, render)["result"].as_str().unwrap().starts_with("<svg"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut server = RpcServer::default();
+        assert_eq!(call(&mut server, "{not json")["error"]["code"], PARSE_ERROR);
+        assert_eq!(call(&mut server, "[]")["error"]["code"], INVALID_REQUEST);
+        assert_eq!(call(&mut server, r#"{"id": 1, "method": "count"}"#)["error"]["code"], INVALID_REQUEST);
+        assert_eq!(call(&mut server, r#"{"jsonrpc": "2.0", "id": 1, "method": "fly"}"#)["error"]["code"], METHOD_NOT_FOUND);
+
+        let error = call(&mut server, r#"{"jsonrpc": "2.0", "id": 1, "method": "count", "params": {"columns": 5, "pieces": [0]}}"#);
+        assert_eq!(error["error"]["code"], INVALID_PARAMS);
+        assert_eq!(error["error"]["message"], "invalid game: at least 2 pieces are needed, got 1");
+        assert_eq!(error["id"], 1);
+        let error = call(&mut server, r#"{"jsonrpc": "2.0", "id": 1, "method": "count", "params": {"colums": 5}}"#);
+        assert_eq!(error["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_notifications_and_batches() {
+        let mut server = RpcServer::default();
+        assert_eq!(server.handle_line(&format!(r#"{{"jsonrpc": "2.0", "method": "count", "params": {{{GAME}}}}}"#)), None);
+
+        let batch = call(&mut server, &format!(
+            r#"[{{"jsonrpc": "2.0", "id": 1, "method": "count", "params": {{{GAME}}}}}, {{"jsonrpc": "2.0", "method": "count"}}, 3]"#
+        ));
+        let batch = batch.as_array().unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0]["result"]["count"], 36);
+        assert_eq!(batch[1]["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let mut server = RpcServer::default();
+        let invalid = r#"{"jsonrpc": "2.0", "id": 1, "method": "count", "params": {"columns": 3, "pieces": [0, 1]}}"#;
+        assert_eq!(call(&mut server, invalid)["error"]["code"], INVALID_PARAMS);
+        assert!(server.cache.is_empty());
+
+        // 20 distinct games: 4 board widths in 5 palettes
+        for palette in Palette::ALL {
+            for columns in [1, 2, 4, 8] {
+                let request = format!(
+                    r#"{{"jsonrpc": "2.0", "id": 1, "method": "count", "params": {{"columns": {columns}, "pieces": ["PinkBar4", "PinkBar4"], "palette": "{}"}}}}"#,
+                    palette.name()
+                );
+                assert!(call(&mut server, &request)["result"]["count"].as_u64().unwrap() > 0);
+            }
+        }
+        assert_eq!(server.cache.len(), CACHE_SIZE);
+    }
+
+    #[test]
+    fn test_run() {
+        let input = format!("{{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"count\", \"params\": {{{GAME}}}}}\n\n");
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "{\"jsonrpc\":\"2.0\",\"result\":{\"count\":36},\"id\":1}\n");
+    }
+}
@@ -0,0 +1,472 @@
+use std::collections::VecDeque;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use gtp_lib::dead_end;
+use gtp_lib::game_resolver::{piece_index, SolverSession};
+use gtp_lib::palette::Palette;
+use gtp_lib::svg_renderer::{svg_challenge_card, svg_challenge_card_back, svg_contact_sheet, ContactSheetOptions};
+use gtp_lib::{Game, Piece};
+use nalgebra::DMatrix;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::cli::parse_piece;
+use crate::commands::catalog_pieces;
+use crate::display::rows;
+
+/// Maximum number of solutions in a page.
+pub const MAX_PAGE_SIZE: usize = 100;
+/// Number of solutions in a page when `size` is not given.
+const DEFAULT_PAGE_SIZE: usize = 10;
+/// Number of boards per row of the SVG contact sheet of a page.
+const SHEET_COLUMNS: usize = 4;
+/// Placements tried between two checks of the time budget, well under a millisecond of search.
+const PLACEMENTS_PER_SLICE: usize = 10_000;
+
+const JSON: &str = "application/json";
+const SVG: &str = "image/svg+xml";
+
+/// Settings of the server.
+#[derive(Debug, Clone, Copy)]
+pub struct ServerOptions {
+    /// Maximum time spent solving for one request.
+    pub budget: Duration,
+}
+
+/// A response, before it is sent.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reply {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Reply {
+    fn json(value: &impl Serialize) -> Self {
+        Self { status: 200, content_type: JSON, body: serde_json::to_string(value).expect("Cannot serialize response") }
+    }
+
+    fn svg(svg: String) -> Self {
+        Self { status: 200, content_type: SVG, body: svg }
+    }
+
+    fn error(status: u16, message: impl std::fmt::Display) -> Self {
+        let body = ErrorBody { error: message.to_string() };
+        Self { status, ..Self::json(&body) }
+    }
+}
+
+fn bad_request(error: String) -> Reply {
+    Reply::error(400, error)
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// Response of `/validate`.
+#[derive(Serialize)]
+struct Validation {
+    valid: bool,
+    columns: u32,
+    rows: u32,
+    cells: u32,
+    error: Option<String>,
+}
+
+/// Response of `/count`.
+#[derive(Serialize)]
+struct Count {
+    count: u32,
+    time_ms: f64,
+}
+
+/// Response of `/solutions` in the `json` format.
+#[derive(Serialize)]
+struct SolutionPage {
+    page: usize,
+    size: usize,
+    /// Piece ids per cell of each solution.
+    solutions: Vec<Vec<Vec<Option<usize>>>>,
+    time_ms: f64,
+}
+
+/// Query parameters of a request, percent-decoded.
+struct Query(Vec<(String, String)>);
+
+impl Query {
+    fn parse(query: &str) -> Result<Self, String> {
+        query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                Ok((decode(key)?, decode(value)?))
+            })
+            .collect::<Result<_, String>>()
+            .map(Self)
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+
+    /// Fails on parameters not in `allowed`, so that typos are not silently ignored.
+    fn check(&self, allowed: &[&str]) -> Result<(), String> {
+        match self.0.iter().find(|(name, _)| !allowed.contains(&name.as_str())) {
+            Some((name, _)) => Err(format!("unknown parameter '{name}', expected one of {}", allowed.join(", "))),
+            None => Ok(()),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&self, key: &str, default: Option<T>) -> Result<T, String> {
+        match self.get(key) {
+            Some(value) => value.parse().map_err(|_| format!("invalid value '{value}' for '{key}'")),
+            None => default.ok_or_else(|| format!("missing parameter '{key}'")),
+        }
+    }
+
+    fn pieces(&self) -> Result<Vec<Piece>, String> {
+        self.get("pieces").ok_or("missing parameter 'pieces'")?.split(',').map(parse_piece).collect()
+    }
+
+    fn palette(&self) -> Result<Palette, String> {
+        match self.get("palette") {
+            Some(name) => Palette::from_name(name).ok_or_else(|| format!("unknown palette '{name}'")),
+            None => Ok(Palette::Classic),
+        }
+    }
+
+    /// Builds the game of the `columns`, `pieces` and `palette` parameters.
+    fn game(&self) -> Result<Game, String> {
+        let mut game = Game { columns: self.number("columns", None)?, pieces: self.pieces()? };
+        self.palette()?.apply(&mut game);
+        Ok(game)
+    }
+}
+
+/// Decodes a percent-encoded query component, `+` standing for a space.
+fn decode(text: &str) -> Result<String, String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut input = text.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = input.by_ref().take(2).collect();
+                let value = std::str::from_utf8(&hex).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                bytes.push(value.filter(|_| hex.len() == 2).ok_or_else(|| format!("invalid escape in '{text}'"))?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| format!("invalid UTF-8 in '{text}'"))
+}
+
+/// A search for the solutions of a game, run in slices on the request thread and stopped
+/// as soon as the time budget of the request is exceeded.
+///
+/// Solutions come in the same order as [`gtp_lib::GameResolverTrait::resolve_page`].
+struct Search {
+    session: SolverSession,
+    /// Solutions found by the last slice and not returned yet.
+    found: VecDeque<DMatrix<u32>>,
+    /// `None` when the budget is too large to ever run out.
+    deadline: Option<Instant>,
+    budget: Duration,
+}
+
+impl Search {
+    fn new(game: &Game, budget: Duration) -> Self {
+        let deadline = Instant::now().checked_add(budget);
+        Self { session: SolverSession::new(game), found: VecDeque::new(), deadline, budget }
+    }
+
+    /// Fails once the budget is exceeded.
+    fn check(&self) -> Result<(), Reply> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                Err(Reply::error(503, format_args!("the time budget of {} ms was exceeded", self.budget.as_millis())))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the next solution, or `None` once they were all found.
+    fn next(&mut self) -> Result<Option<DMatrix<u32>>, Reply> {
+        while self.found.is_empty() && !self.session.is_done() {
+            self.check()?;
+            self.found.extend(self.session.step(PLACEMENTS_PER_SLICE));
+        }
+        Ok(self.found.pop_front())
+    }
+
+    /// Returns up to `size` solutions, after skipping the first `skip` ones.
+    fn page(mut self, skip: usize, size: usize) -> Result<Vec<DMatrix<u32>>, Reply> {
+        for _ in 0..skip {
+            if self.next()?.is_none() {
+                return Ok(Vec::new());
+            }
+        }
+        let mut solutions = Vec::new();
+        while solutions.len() < size {
+            let Some(solution) = self.next()? else { break };
+            solutions.push(solution);
+        }
+        Ok(solutions)
+    }
+
+    /// Runs the search to the end, returning the number of solutions.
+    fn count(mut self) -> Result<usize, Reply> {
+        while !self.session.is_done() {
+            self.check()?;
+            self.session.step(PLACEMENTS_PER_SLICE);
+        }
+        Ok(self.session.solutions_found())
+    }
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Builds the game of the query, and checks that it can be solved.
+fn valid_game(query: &Query) -> Result<Game, Reply> {
+    let game = query.game().map_err(bad_request)?;
+    game.validate().map_err(|error| Reply::error(400, format_args!("invalid game: {error}")))?;
+    Ok(game)
+}
+
+/// Answers a request to `url`, such as `/count?columns=5&pieces=0,5,4,9,8`.
+pub fn route(method: &Method, url: &str, options: ServerOptions) -> Reply {
+    if *method != Method::Get {
+        return Reply::error(405, "only GET requests are supported");
+    }
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = match Query::parse(query) {
+        Ok(query) => query,
+        Err(error) => return bad_request(error),
+    };
+    let reply = match path {
+        "/pieces" => pieces(&query),
+        "/validate" => validate(&query),
+        "/count" => count(&query, options),
+        "/solutions" => solutions(&query, options),
+        "/card" => card(&query, options),
+        _ => Err(Reply::error(404, format_args!("unknown path '{path}'"))),
+    };
+    reply.unwrap_or_else(|error| error)
+}
+
+/// Lists the pieces of a game, or of the full box when no pieces are given.
+fn pieces(query: &Query) -> Result<Reply, Reply> {
+    query.check(&["pieces", "palette"]).map_err(bad_request)?;
+    let pieces = if query.get("pieces").is_some() { query.pieces() } else { Ok(Game::game_with_all_pieces().pieces) };
+    let mut game = Game { columns: 1, pieces: pieces.map_err(bad_request)? };
+    query.palette().map_err(bad_request)?.apply(&mut game);
+    Ok(Reply::json(&catalog_pieces(&game)))
+}
+
+fn validate(query: &Query) -> Result<Reply, Reply> {
+    query.check(&["columns", "pieces"]).map_err(bad_request)?;
+    let game = query.game().map_err(bad_request)?;
+    let error = game.validate().err().map(|error| error.to_string());
+    Ok(Reply::json(&Validation {
+        valid: error.is_none(),
+        columns: game.columns,
+        rows: game.rows(),
+        cells: game.cells(),
+        error,
+    }))
+}
+
+fn count(query: &Query, options: ServerOptions) -> Result<Reply, Reply> {
+    query.check(&["columns", "pieces"]).map_err(bad_request)?;
+    let game = valid_game(query)?;
+    let start = Instant::now();
+    let count = Search::new(&game, options.budget).count()?;
+    let count = u32::try_from(count).map_err(|_| Reply::error(500, "the solution count exceeds u32::MAX"))?;
+    Ok(Reply::json(&Count { count, time_ms: elapsed_ms(start) }))
+}
+
+fn solutions(query: &Query, options: ServerOptions) -> Result<Reply, Reply> {
+    query.check(&["columns", "pieces", "palette", "page", "size", "format"]).map_err(bad_request)?;
+    let game = valid_game(query)?;
+    let page: usize = query.number("page", Some(0)).map_err(bad_request)?;
+    let size = query.number("size", Some(DEFAULT_PAGE_SIZE)).map_err(bad_request)?;
+    if !(1..=MAX_PAGE_SIZE).contains(&size) {
+        return Err(Reply::error(400, format_args!("size must be between 1 and {MAX_PAGE_SIZE}, got {size}")));
+    }
+    let svg = match query.get("format") {
+        None | Some("json") => false,
+        Some("svg") => true,
+        Some(format) => return Err(Reply::error(400, format_args!("unknown format '{format}', expected json or svg"))),
+    };
+
+    let start = Instant::now();
+    let solutions = Search::new(&game, options.budget).page(page.saturating_mul(size), size)?;
+    if svg {
+        let options = ContactSheetOptions { columns: SHEET_COLUMNS, first_label: page.saturating_mul(size).saturating_add(1), ..ContactSheetOptions::default() };
+        return Ok(Reply::svg(svg_contact_sheet(&solutions, &options)));
+    }
+    Ok(Reply::json(&SolutionPage {
+        page,
+        size,
+        solutions: solutions.iter().map(|solution: &DMatrix<u32>| rows(solution, piece_index)).collect(),
+        time_ms: elapsed_ms(start),
+    }))
+}
+
+/// Renders the front of the challenge card of a game, or its back with the first solution.
+fn card(query: &Query, options: ServerOptions) -> Result<Reply, Reply> {
+    query.check(&["columns", "pieces", "palette", "side"]).map_err(bad_request)?;
+    let game = valid_game(query)?;
+    match query.get("side") {
+        None | Some("front") => Ok(Reply::svg(svg_challenge_card(&game))),
+        Some("back") => {
+            let mut search = Search::new(&game, options.budget);
+            let Some(solution) = search.next()? else {
+                // Only the quick checks run here, never another search, and only within the budget
+                search.check()?;
+                let reason = dead_end::explain_unsolvable(&game, 0, &game.piece_ids());
+                return Err(Reply::error(422, format_args!("the game has no solution: {reason}")));
+            };
+            Ok(Reply::svg(svg_challenge_card_back(&game, &solution)))
+        }
+        Some(side) => Err(Reply::error(400, format_args!("unknown side '{side}', expected front or back"))),
+    }
+}
+
+fn respond(request: Request, options: ServerOptions) -> io::Result<()> {
+    let reply = route(request.method(), request.url(), options);
+    let header = Header::from_bytes("Content-Type", reply.content_type).expect("Invalid header");
+    request.respond(Response::from_string(reply.body).with_status_code(reply.status).with_header(header))
+}
+
+/// Answers the requests of `server` on `threads` threads, until it stops accepting requests.
+pub fn serve(server: &Server, threads: usize, options: ServerOptions) {
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                while let Ok(request) = server.recv() {
+                    if let Err(error) = respond(request, options) {
+                        eprintln!("warning: cannot send the response: {error}");
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+
+    const OPTIONS: ServerOptions = ServerOptions { budget: Duration::from_secs(10) };
+    const GAME: &str = "columns=5&pieces=RedSquare1,OrangeBar3,BrownL3,YellowZigZag4,BlueT4";
+
+    fn get(url: &str) -> Reply {
+        route(&Method::Get, url, OPTIONS)
+    }
+
+    fn query_game(query: &str) -> Game {
+        Query::parse(query).unwrap().game().unwrap()
+    }
+
+    fn json(reply: &Reply) -> serde_json::Value {
+        assert_eq!(reply.content_type, JSON);
+        serde_json::from_str(&reply.body).unwrap()
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("BlueT4%2CRedSquare1").unwrap(), "BlueT4,RedSquare1");
+        assert_eq!(decode("a+b").unwrap(), "a b");
+        assert!(decode("%2").is_err());
+        assert!(decode("%zz").is_err());
+    }
+
+    #[test]
+    fn test_pieces_and_validate() {
+        assert_eq!(json(&get("/pieces")).as_array().unwrap().len(), 18);
+        assert_eq!(json(&get("/pieces?pieces=0,BlueT4"))[1]["name"], "BlueT4");
+
+        let valid = json(&get(&format!("/validate?{GAME}")));
+        assert_eq!(valid["valid"], true);
+        assert_eq!(valid["rows"], 3);
+        let invalid = json(&get("/validate?columns=4&pieces=0,1"));
+        assert_eq!(invalid["valid"], false);
+        assert!(invalid["error"].as_str().unwrap().contains("cells"));
+    }
+
+    #[test]
+    fn test_count_and_solutions() {
+        assert_eq!(json(&get(&format!("/count?{GAME}")))["count"], 36);
+
+        let page = json(&get(&format!("/solutions?{GAME}&page=3&size=10")));
+        assert_eq!(page["solutions"].as_array().unwrap().len(), 6);
+        assert_eq!(page["solutions"][0].as_array().unwrap().len(), 3);
+
+        let svg = get(&format!("/solutions?{GAME}&size=2&format=svg"));
+        assert_eq!((svg.status, svg.content_type), (200, SVG));
+        assert!(svg.body.starts_with("<svg"));
+
+        let back = get(&format!("/card?{GAME}&side=back"));
+        assert_eq!((back.status, back.content_type), (200, SVG));
+        assert_eq!(get("/card?columns=4&pieces=PinkBar4,VioletSquare4&side=back").status, 422);
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        assert_eq!(get("/nothing").status, 404);
+        assert_eq!(route(&Method::Post, "/count", OPTIONS).status, 405);
+        assert_eq!(get("/count?columns=5").status, 400);
+        assert_eq!(get("/count?columns=five&pieces=0,1").status, 400);
+        assert_eq!(get("/count?columns=5&pieces=0,99").status, 400);
+        assert_eq!(get("/count?columns=5&pieces=0").status, 400);
+        assert_eq!(get(&format!("/count?{GAME}&colums=5")).status, 400);
+        assert_eq!(get(&format!("/solutions?{GAME}&size=1000")).status, 400);
+        assert_eq!(get(&format!("/solutions?{GAME}&format=png")).status, 400);
+        let error = json(&get("/count?columns=5&pieces=0"));
+        assert_eq!(error["error"], "invalid game: at least 2 pieces are needed, got 1");
+    }
+
+    #[test]
+    fn test_time_budget() {
+        // 12 pieces on a 5x11 board, far too many solutions to count in 20 ms
+        let slow = "/count?columns=5&pieces=6,7,8,9,10,11,12,13,14,15,16,17";
+        let start = Instant::now();
+        let reply = route(&Method::Get, slow, ServerOptions { budget: Duration::from_millis(20) });
+        assert_eq!(reply.status, 503);
+        // The search stops at the deadline instead of running on
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let game = query_game(GAME);
+        assert_eq!(Search::new(&game, Duration::ZERO).count().unwrap_err().status, 503);
+        assert_eq!(Search::new(&game, Duration::MAX).count().unwrap(), 36);
+        assert_eq!(Search::new(&game, Duration::MAX).page(30, 10).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_serve() {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let address = server.server_addr().to_ip().unwrap();
+        let serving = Arc::clone(&server);
+        thread::spawn(move || serve(&serving, 1, OPTIONS));
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET /count?{GAME} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("Content-Type: application/json"));
+        assert!(response.contains("\"count\":36"));
+        server.unblock();
+    }
+}
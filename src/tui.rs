@@ -0,0 +1,346 @@
+use std::io;
+
+use gtp_lib::{Game, GameError, GameResolver, GameResolverTrait, PieceName};
+use gtp_lib::palette::Palette;
+use nalgebra::DMatrix;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cli::GameArgs;
+use crate::display::{from_rgb_u32, BLOCK_CHAR, DEFAULT_COLOR, DISPLAY_SIZE};
+
+/// Number of solutions fetched with each call to `resolve_page`.
+const PAGE_SIZE: usize = 10;
+
+/// Panel receiving the key presses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Pieces,
+    Solutions,
+}
+
+/// State of the terminal UI: the pieces picked from the box and the solutions loaded so far.
+pub struct App {
+    /// The full box, colored with the selected palette.
+    all: Game,
+    selected: Vec<bool>,
+    columns: u32,
+    cursor: usize,
+    focus: Focus,
+    /// Solutions of the current selection, loaded page by page.
+    solutions: Vec<DMatrix<u32>>,
+    pages_loaded: usize,
+    /// Whether the last page was shorter than [`PAGE_SIZE`]: there is no solution left to load.
+    exhausted: bool,
+    current: usize,
+    quit: bool,
+}
+
+impl App {
+    /// Creates the UI state, with the pieces and columns of the game selected by `args`.
+    pub fn new(args: &GameArgs) -> Self {
+        let all = args.colored(Game::game_with_all_pieces(), Palette::Terminal);
+        let mut selected = vec![false; all.pieces.len()];
+        for piece in args.game(Palette::Terminal).pieces {
+            let name = PieceName::from_matrix(&piece.matrix);
+            let index = (0..all.pieces.len())
+                .find(|&index| !selected[index] && PieceName::from_matrix(&all.pieces[index].matrix) == name);
+            if let Some(index) = index {
+                selected[index] = true;
+            }
+        }
+
+        Self {
+            all,
+            selected,
+            columns: args.game(Palette::Terminal).columns,
+            cursor: 0,
+            focus: Focus::Pieces,
+            solutions: Vec::new(),
+            pages_loaded: 0,
+            exhausted: false,
+            current: 0,
+            quit: false,
+        }
+    }
+
+    /// Returns the game made of the selected pieces.
+    fn game(&self) -> Game {
+        let piece_ids = (0..self.selected.len()).filter(|&index| self.selected[index]).collect();
+        Game { columns: self.columns, ..Game::game_from_game(&self.all, piece_ids) }
+    }
+
+    /// Returns the validity of the selection, as a message and whether the game can be solved.
+    fn status(&self) -> (String, bool) {
+        let game = self.game();
+        match game.validate() {
+            Ok(()) => (format!("Valid: {}x{} board", game.rows(), game.columns), true),
+            Err(GameError::MissingCells { missing }) => (format!("Missing {missing} cells to fill the last row"), false),
+            Err(GameError::NoColumns | GameError::NotEnoughPieces { .. }) => ("Select at least 2 pieces".to_string(), false),
+            Err(error) => (format!("Invalid: {error}"), false),
+        }
+    }
+
+    /// Forgets the solutions of the previous selection.
+    fn reset_solutions(&mut self) {
+        self.solutions.clear();
+        self.pages_loaded = 0;
+        self.exhausted = false;
+        self.current = 0;
+    }
+
+    /// Loads the next page of solutions, if the game is valid and solutions are left.
+    fn load_next_page(&mut self) {
+        if self.exhausted {
+            return;
+        }
+        let game = self.game();
+        if game.validate().is_err() {
+            self.exhausted = true;
+            return;
+        }
+        let page = GameResolver.resolve_page(&game, self.pages_loaded, PAGE_SIZE);
+        self.pages_loaded += 1;
+        self.exhausted = page.len() < PAGE_SIZE;
+        self.solutions.extend(page);
+    }
+
+    /// Moves to the solution at `index`, loading pages as needed, or to the last one if there are fewer.
+    fn go_to_solution(&mut self, index: usize) {
+        while index >= self.solutions.len() && !self.exhausted {
+            self.load_next_page();
+        }
+        self.current = index.min(self.solutions.len().saturating_sub(1));
+    }
+
+    fn handle_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Pieces => Focus::Solutions,
+                    Focus::Solutions => Focus::Pieces,
+                };
+            }
+            KeyCode::Char('+') => {
+                self.columns += 1;
+                self.reset_solutions();
+            }
+            KeyCode::Char('-') if self.columns > 1 => {
+                self.columns -= 1;
+                self.reset_solutions();
+            }
+            _ => match self.focus {
+                Focus::Pieces => self.handle_pieces_key(key),
+                Focus::Solutions => self.handle_solutions_key(key),
+            },
+        }
+
+        // Solutions are only computed once they are looked at
+        if self.focus == Focus::Solutions && self.solutions.is_empty() {
+            self.load_next_page();
+        }
+    }
+
+    fn handle_pieces_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (self.cursor + 1).min(self.selected.len() - 1),
+            KeyCode::Char(' ') => {
+                self.selected[self.cursor] = !self.selected[self.cursor];
+                self.reset_solutions();
+            }
+            KeyCode::Enter => self.focus = Focus::Solutions,
+            _ => {}
+        }
+    }
+
+    fn handle_solutions_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Right | KeyCode::Char('l' | 'n') => self.go_to_solution(self.current + 1),
+            KeyCode::Left | KeyCode::Char('h' | 'p') => self.current = self.current.saturating_sub(1),
+            KeyCode::PageDown => self.go_to_solution(self.current + PAGE_SIZE),
+            KeyCode::PageUp => self.current = self.current.saturating_sub(PAGE_SIZE),
+            KeyCode::Home => self.current = 0,
+            _ => {}
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, help] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [pieces, right] = Layout::horizontal([Constraint::Length(34), Constraint::Min(0)]).areas(main);
+        let [status, solution] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(right);
+
+        let focused = |focus: Focus| {
+            if self.focus == focus { Style::new().fg(Color::Yellow) } else { Style::new() }
+        };
+
+        let items: Vec<ListItem> = self
+            .all
+            .pieces
+            .iter()
+            .zip(&self.selected)
+            .map(|(piece, &selected)| {
+                let (r, g, b) = from_rgb_u32(piece.tui_color);
+                let name = PieceName::from_matrix(&piece.matrix).map_or("Custom piece", PieceName::description);
+                ListItem::new(Line::from(vec![
+                    Span::raw(if selected { "[x] " } else { "[ ] " }),
+                    Span::styled(BLOCK_CHAR.repeat(DISPLAY_SIZE * 2), Style::new().fg(Color::Rgb(r, g, b))),
+                    Span::raw(format!(" {} {name}", piece.cells())),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Pieces ").border_style(focused(Focus::Pieces)))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        let mut list_state = ListState::default().with_selected(Some(self.cursor));
+        frame.render_stateful_widget(list, pieces, &mut list_state);
+
+        let (message, valid) = self.status();
+        let game = self.game();
+        let status_line = Line::from(vec![
+            Span::styled(message, Style::new().fg(if valid { Color::Green } else { Color::Red })),
+            Span::raw(format!("  {} pieces, {} cells", game.pieces.len(), game.cells())),
+        ]);
+        frame.render_widget(Paragraph::new(status_line).block(Block::bordered().title(" Game ")), status);
+
+        let title = match self.solutions.len() {
+            _ if self.focus == Focus::Pieces && self.solutions.is_empty() => " Solutions ".to_string(),
+            0 => " No solution ".to_string(),
+            loaded => {
+                let more = if self.exhausted { "" } else { "+" };
+                format!(" Solution {} of {loaded}{more} ", self.current + 1)
+            }
+        };
+        let body = self.solutions.get(self.current).map_or_else(
+            || vec![Line::raw(if valid { "Press Tab to browse the solutions" } else { "" })],
+            block_lines,
+        );
+        frame.render_widget(
+            Paragraph::new(body).block(Block::bordered().title(title).border_style(focused(Focus::Solutions))),
+            solution,
+        );
+
+        let keys = match self.focus {
+            Focus::Pieces => "↑↓ move  Space toggle  +/- columns  Tab/Enter solutions  q quit",
+            Focus::Solutions => "←→ previous/next  PgUp/PgDn ±10  Home first  +/- columns  Tab pieces  q quit",
+        };
+        frame.render_widget(Paragraph::new(keys).style(Style::new().add_modifier(Modifier::DIM)), help);
+    }
+}
+
+/// Renders a matrix as colored blocks, like [`crate::display::display`].
+fn block_lines(matrix: &DMatrix<u32>) -> Vec<Line<'static>> {
+    let block = BLOCK_CHAR.repeat(DISPLAY_SIZE * 2);
+    matrix
+        .row_iter()
+        .flat_map(|row| {
+            let spans: Vec<Span> = row
+                .iter()
+                .map(|&color| {
+                    let (r, g, b) = from_rgb_u32(if color > 0 { color } else { DEFAULT_COLOR });
+                    Span::styled(block.clone(), Style::new().fg(Color::Rgb(r, g, b)))
+                })
+                .collect();
+            std::iter::repeat_n(Line::from(spans), DISPLAY_SIZE)
+        })
+        .collect()
+}
+
+/// Runs the terminal UI until the user quits.
+pub fn run(args: &GameArgs) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, App::new(args));
+    ratatui::restore();
+    result
+}
+
+fn run_app(terminal: &mut DefaultTerminal, mut app: App) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key.code);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        game: GameArgs,
+    }
+
+    fn app(args: &[&str]) -> App {
+        let cli = TestCli::parse_from(std::iter::once("gtp").chain(args.iter().copied()));
+        App::new(&cli.game)
+    }
+
+    #[test]
+    fn test_selection_from_args() {
+        let app = app(&["--pieces", "RedSquare1,TanBar2,RedSquare1", "--columns", "2"]);
+        assert_eq!(app.selected.iter().filter(|&&selected| selected).count(), 3);
+        assert!(app.selected[0] && app.selected[1] && app.selected[2]);
+        assert_eq!(app.columns, 2);
+    }
+
+    #[test]
+    fn test_status() {
+        let mut app = app(&["--pieces", "RedSquare1,TanBar2", "--columns", "2"]);
+        assert_eq!(app.status(), ("Missing 1 cells to fill the last row".to_string(), false));
+        app.selected[1] = true;
+        assert_eq!(app.status(), ("Valid: 2x2 board".to_string(), true));
+    }
+
+    #[test]
+    fn test_solutions_are_loaded_lazily() {
+        // The default game has 36 solutions
+        let mut app = app(&[]);
+        assert!(app.solutions.is_empty());
+
+        app.handle_key(KeyCode::Tab);
+        assert_eq!(app.solutions.len(), PAGE_SIZE);
+
+        for _ in 0..PAGE_SIZE {
+            app.handle_key(KeyCode::Right);
+        }
+        assert_eq!(app.current, PAGE_SIZE);
+        assert_eq!(app.solutions.len(), 2 * PAGE_SIZE);
+
+        app.handle_key(KeyCode::PageDown);
+        app.handle_key(KeyCode::PageDown);
+        app.handle_key(KeyCode::PageDown);
+        assert!(app.exhausted);
+        assert_eq!(app.solutions.len(), 36);
+        assert_eq!(app.current, 35);
+
+        // Changing the selection drops the solutions
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Char(' '));
+        assert!(app.solutions.is_empty());
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut app = app(&[]);
+        app.handle_key(KeyCode::Tab);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Solution 1 of 10+"));
+        assert!(screen.contains("Valid: 3x5 board"));
+    }
+}
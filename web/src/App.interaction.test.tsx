@@ -9,7 +9,7 @@ vi.mock('lib-wasm', () => {
     JSGame: {
       game_with_all_pieces: () => ({
         pieces: [
-          { id: 1, cells: 10, matrix: { svg: '<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect width="10" height="10" fill="black"/></svg>', width: 2 } },
+          { id: 1, cells: 10, svg: '<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect width="10" height="10" fill="black"/></svg>', width: 2 },
         ],
       }),
       game_from_game: (_game: any, ids: Uint32Array) => {
@@ -8,11 +8,32 @@ type Props = {
 }
 
 export default class PieceView extends React.Component<Props, {}> {
+  // The image URL is only created again when the SVG of the piece changes, for example with the palette
+  private svg: string | null = null;
+  private url: string | null = null;
+
+  private imageUrl(): string {
+    const svg = this.props.piece.svg;
+    if (svg !== this.svg || this.url === null) {
+      if (this.url) {
+        URL.revokeObjectURL(this.url);
+      }
+      this.svg = svg;
+      this.url = URL.createObjectURL(new Blob([svg], { type: "image/svg+xml" }));
+    }
+    return this.url;
+  }
+
+  componentWillUnmount() {
+    if (this.url) {
+      URL.revokeObjectURL(this.url);
+      this.url = null;
+    }
+  }
+
   render() {
-    const svg = new Blob([this.props.piece.matrix.svg], { type: "image/svg+xml" });
-    const url = URL.createObjectURL(svg);
     // Pass the width in "cells" to CSS via a custom property
-    const style = { "--piece-width": this.props.piece.matrix.width } as React.CSSProperties;
-    return <img src={url} className="piece-image" style={style} alt='' />
+    const style = { "--piece-width": this.props.piece.width } as React.CSSProperties;
+    return <img src={this.imageUrl()} className="piece-image" style={style} alt='' />
   }
 }
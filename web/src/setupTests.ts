@@ -12,3 +12,6 @@ if (!globalThis.URL) {
 if (!globalThis.URL.createObjectURL) {
 	globalThis.URL.createObjectURL = () => 'blob:mock-url';
 }
+if (!globalThis.URL.revokeObjectURL) {
+	globalThis.URL.revokeObjectURL = () => {};
+}